@@ -1,6 +1,72 @@
-use crate::script::ScriptHandle;
+use crate::battle::{self, BattleOutcome};
+use crate::dialog::{self, DialogFrame};
 use crate::game_context::GameContext;
+use crate::geometry::OverworldRect;
+use crate::overworld::choreography;
+use crate::overworld::follower::{self, CreationParams};
+use crate::overworld::move_trait::Direction;
+use crate::overworld::multiside::DirectionalTexturePaths;
+use crate::overworld::room::EntityId;
+use crate::script::ScriptHandle;
+use euclid::vec2;
+use log::warn;
+
+// The room's init script runs on every visit, but the intro is only shown once.
+const INTRO_SEEN: &str = "home.room.intro_seen";
+
+fn kit() -> CreationParams {
+    let texture = |direction: &str| format!("overworld/party/kit/{}", direction);
+    CreationParams {
+        name: String::from("kit"),
+        textures: DirectionalTexturePaths {
+            left: texture("left"),
+            right: texture("right"),
+            forward: texture("forward"),
+            backward: texture("backward"),
+        },
+        hitbox: OverworldRect::new([-16.0, -28.0].into(), [16.0, 36.0].into()),
+        distance: 48.0,
+    }
+}
 
-pub async fn main(_script_handle: ScriptHandle, _context: GameContext) {
-    println!("Hello world from script!");
+pub async fn main(_script_handle: ScriptHandle, context: GameContext) {
+    if context.flags.lock().unwrap().borrow().is_set(INTRO_SEEN) {
+        return;
+    }
+    // The player takes a few steps and turns to the camera before the intro.
+    let walked = async {
+        let start = choreography::entity_position(&context, &EntityId::Player)?;
+        choreography::walk_path(
+            &context,
+            EntityId::Player,
+            vec![start - vec2(40.0, 0.0)],
+            120.0,
+        )
+        .await?;
+        choreography::face(&context, EntityId::Player, Direction::Backward).await
+    };
+    if let Err(e) = walked.await {
+        warn!("The player could not walk into place for the intro: {}", e);
+    }
+    dialog::show(
+        &context,
+        vec![
+            DialogFrame::new(None, "dialog.home.room.intro"),
+            DialogFrame::new(Some("characters.kit"), "dialog.home.room.kit_joins"),
+        ],
+    )
+    .await;
+    let reply = match battle::start(&context, "kit").await {
+        BattleOutcome::Won => DialogFrame::new(Some("characters.kit"), "dialog.home.room.kit_lost"),
+        BattleOutcome::Spared => {
+            DialogFrame::new(Some("characters.kit"), "dialog.home.room.kit_spared")
+        }
+        BattleOutcome::Fled => {
+            DialogFrame::new(Some("characters.kit"), "dialog.home.room.kit_chased")
+        }
+    };
+    dialog::show(&context, vec![reply]).await;
+    // Kit stays in the party until the neighbour asks for help in the yard.
+    follower::add_follower(&context, &kit());
+    context.flags.lock().unwrap().borrow_mut().raise(INTRO_SEEN);
 }
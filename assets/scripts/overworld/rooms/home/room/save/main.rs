@@ -0,0 +1,13 @@
+use crate::dialog::{self, DialogFrame};
+use crate::game_context::GameContext;
+use crate::save;
+use crate::script::ScriptHandle;
+
+pub async fn main(_script_handle: ScriptHandle, context: GameContext) {
+    dialog::show(
+        &context,
+        vec![DialogFrame::new(None, "dialog.home.room.save")],
+    )
+    .await;
+    save::save_game(&context);
+}
@@ -0,0 +1,66 @@
+use crate::dialog::{self, DialogFrame};
+use crate::game_context::GameContext;
+use crate::overworld::camera;
+use crate::overworld::choreography::{self, Cutscene};
+use crate::overworld::follower;
+use crate::overworld::navigation::{self, NavigationError};
+use crate::overworld::room::EntityId;
+use crate::script::ScriptHandle;
+use euclid::vec2;
+use log::warn;
+use std::time::Duration;
+
+/// How fast the camera pans, in units per second.
+const CAMERA_SPEED: f32 = 400.0;
+/// The neighbour hurries over to catch the player.
+const HURRY: f32 = 1.5;
+
+pub async fn main(_script_handle: ScriptHandle, context: GameContext) {
+    let _cutscene = Cutscene::begin(&context);
+    if let Err(e) = meet_neighbour(&context).await {
+        warn!("The crossroads cutscene was cut short: {}", e);
+    }
+    // Pans leave the camera in place, so it has to be given back to the player.
+    camera::follow_player(&context);
+}
+
+// The camera shows the neighbour noticing the player at the crossroads. Then the neighbour comes
+// over and says hello.
+async fn meet_neighbour(context: &GameContext) -> Result<(), NavigationError> {
+    let neighbour = EntityId::Npc(String::from("neighbour"));
+    let position = choreography::entity_position(context, &neighbour)?;
+    camera::pan_to(context, position, CAMERA_SPEED).await;
+    choreography::emote(
+        context,
+        neighbour.clone(),
+        "overworld/emotes/exclamation",
+        Duration::from_millis(800),
+    )
+    .await?;
+    let player = choreography::entity_position(context, &EntityId::Player)?;
+    camera::pan_to(context, player, CAMERA_SPEED).await;
+    camera::follow_player(context);
+    let usual_speed = choreography::set_speed_multiplier(context, &neighbour, HURRY)?;
+    let walked = navigation::walk_to(context, neighbour.clone(), player + vec2(80.0, 0.0)).await;
+    choreography::set_speed_multiplier(context, &neighbour, usual_speed)?;
+    walked?;
+    choreography::face_entity(context, neighbour.clone(), EntityId::Player).await?;
+    choreography::face_entity(context, EntityId::Player, neighbour.clone()).await?;
+    let mut frames = vec![DialogFrame::new(
+        Some("characters.neighbour"),
+        "dialog.home.yard.greeting",
+    )];
+    // Kit stays behind to help, if they came along.
+    if follower::remove_follower(context, "kit") {
+        frames.push(DialogFrame::new(
+            Some("characters.neighbour"),
+            "dialog.home.yard.ask_for_help",
+        ));
+        frames.push(DialogFrame::new(
+            Some("characters.kit"),
+            "dialog.home.yard.kit_stays",
+        ));
+    }
+    dialog::show(context, frames).await;
+    choreography::animate(context, EntityId::Player, "overworld/player/look_around").await
+}
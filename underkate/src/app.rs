@@ -1,48 +1,88 @@
 use crate::default_runtime::DefaultRuntime;
+use crate::dialog::history::DialogHistory;
+use crate::flags::Flags;
 use crate::game_context::GameContext;
+use crate::geometry::OnScreen;
+use crate::graphics::screen_bounds;
+use crate::inventory::Inventory;
 use crate::overworld::room::{CreationParams, Room};
-use crate::overworld::screen::OverworldScreen;
+use crate::overworld::screen::{BattleRequest, OverworldScreen};
 use crate::resources::{self, ResourceStorageCloneExt};
+use crate::save::{self, SaveData};
 use crate::screen::Screen;
-use crate::ui_event::UiEvent;
+use crate::ui_event::{UiEvent, LANGUAGE_KEY};
 use ggez::conf::WindowSetup;
 use ggez::event::{self, EventHandler};
-use ggez::graphics;
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh};
 use ggez::input::keyboard::{KeyCode, KeyMods};
-use ggez::{Context, ContextBuilder, GameError, GameResult};
+use ggez::{timer, Context, ContextBuilder, GameError, GameResult};
+use log::{info, warn};
 use std::cell::RefCell;
 use std::default::Default;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DIALOG_HISTORY_CAPACITY: usize = 200;
+
+/// How long the overworld takes to fade out before a battle.
+const BATTLE_TRANSITION: Duration = Duration::from_millis(600);
+
+enum ActiveScreen {
+    Overworld,
+    /// The overworld fades out before the battle.
+    BattleTransition {
+        battle: BattleRequest,
+        elapsed: Duration,
+    },
+    Battle(BattleRequest),
+}
 
 struct Underkate {
     game_context: GameContext,
+    active_screen: ActiveScreen,
 }
 
 impl Underkate {
-    pub fn new(ctx: &mut Context) -> Self {
+    // The runtime holds script futures and the overworld screen holds the `Rc` a battle reports
+    // its outcome through, so neither is `Send`. That's fine, since the whole game runs on the
+    // main thread; `GameContext` keeps every piece of shared state the same way.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
         let global_resource_storage = Arc::new(resources::make_global_storage(ctx));
         let runtime = Arc::new(Mutex::new(RefCell::new(DefaultRuntime::new())));
         let overworld_screen = Arc::new(Mutex::new(RefCell::new(OverworldScreen::new())));
+        let localization = Arc::new(Mutex::new(RefCell::new(resources::make_localization())));
+        let dialog_history = Arc::new(Mutex::new(RefCell::new(DialogHistory::new(
+            DIALOG_HISTORY_CAPACITY,
+        ))));
+        let flags = Arc::new(Mutex::new(RefCell::new(Flags::new())));
+        let inventory = Arc::new(Mutex::new(RefCell::new(Inventory::new())));
 
         let game_context = GameContext {
             global_resource_storage,
             runtime,
             overworld_screen,
+            localization,
+            dialog_history,
+            flags,
+            inventory,
         };
 
-        let starting_room = Room::new(
-            CreationParams::from_partial(
-                game_context.global_resource_storage.get_cloned("home/room"),
-                "_",
-            ),
-            game_context.global_resource_storage.as_ref(),
-        );
-        game_context
-            .overworld_screen
-            .lock()
-            .unwrap()
-            .borrow_mut()
-            .load_room(game_context.as_context_ref(), starting_room);
+        let save_path = save::save_path(ctx);
+        let loaded = save_path.exists()
+            && match SaveData::read(&save_path)
+                .and_then(|save_data| save_data.restore(game_context.as_context_ref()))
+            {
+                Ok(()) => true,
+                Err(e) => {
+                    // A broken save must not keep the game from starting.
+                    warn!("Failed to load the save, starting a new game: {}", e);
+                    false
+                }
+            };
+        if !loaded {
+            start_new_game(&game_context)?;
+        }
         game_context
             .runtime
             .lock()
@@ -50,29 +90,130 @@ impl Underkate {
             .borrow_mut()
             .update(ctx);
 
-        Underkate {
+        Ok(Underkate {
             game_context,
+            active_screen: ActiveScreen::Overworld,
+        })
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, ui_event: UiEvent) {
+        let ctx_ref = self.game_context.as_context_ref();
+        match &mut self.active_screen {
+            ActiveScreen::Overworld => self
+                .game_context
+                .overworld_screen
+                .lock()
+                .unwrap()
+                .borrow_mut()
+                .handle_event(ctx, ctx_ref, ui_event),
+            ActiveScreen::BattleTransition { .. } => (),
+            ActiveScreen::Battle(battle) => battle.screen.handle_event(ctx, ctx_ref, ui_event),
         }
     }
 }
 
+fn draw_fade(ctx: &mut Context, opacity: f32) -> GameResult {
+    let fade = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        screen_bounds(ctx).on_screen(),
+        Color::new(0.0, 0.0, 0.0, opacity),
+    )?;
+    graphics::draw(ctx, &fade, DrawParam::new())
+}
+
+fn start_new_game(game_context: &GameContext) -> GameResult {
+    let starting_room = Room::new(
+        CreationParams::from_partial(
+            game_context.global_resource_storage.get_cloned("home/room"),
+            "_",
+        )
+        .map_err(|e| GameError::CustomError(e.to_string()))?,
+        game_context.global_resource_storage.as_ref(),
+    );
+    game_context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .load_room(game_context.as_context_ref(), starting_room);
+    Ok(())
+}
+
 impl EventHandler<GameError> for Underkate {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        self.game_context
-            .overworld_screen
-            .lock()
-            .unwrap()
-            .borrow_mut()
-            .update(ctx, self.game_context.as_context_ref())
+        let (raised_events, save_requested) = {
+            let ctx_ref = self.game_context.as_context_ref();
+            let overworld = self.game_context.overworld_screen.lock().unwrap();
+            let mut overworld = overworld.borrow_mut();
+            let active_screen = std::mem::replace(&mut self.active_screen, ActiveScreen::Overworld);
+            self.active_screen = match active_screen {
+                ActiveScreen::Overworld => {
+                    overworld.update(ctx, ctx_ref)?;
+                    match overworld.take_battle_request() {
+                        Some(battle) => ActiveScreen::BattleTransition {
+                            battle,
+                            elapsed: Duration::ZERO,
+                        },
+                        None => ActiveScreen::Overworld,
+                    }
+                }
+                ActiveScreen::BattleTransition { battle, elapsed } => {
+                    let elapsed = elapsed + timer::delta(ctx);
+                    if elapsed < BATTLE_TRANSITION {
+                        ActiveScreen::BattleTransition { battle, elapsed }
+                    } else {
+                        ActiveScreen::Battle(battle)
+                    }
+                }
+                ActiveScreen::Battle(mut battle) => {
+                    battle.screen.update(ctx, ctx_ref)?;
+                    match battle.screen.outcome() {
+                        Some(outcome) => {
+                            overworld.finish_battle(ctx_ref, battle.source, outcome);
+                            ActiveScreen::Overworld
+                        }
+                        None => ActiveScreen::Battle(battle),
+                    }
+                }
+            };
+            (
+                overworld.take_raised_events(),
+                overworld.take_save_request(),
+            )
+        };
+        if save_requested {
+            SaveData::capture(self.game_context.as_context_ref())
+                .write(&save::save_path(ctx))
+                .map_err(|e| GameError::CustomError(e.to_string()))?;
+        }
+
+        // Scripts lock the screens they control, so they must run while no screen is locked.
+        let runtime = self.game_context.runtime.lock().unwrap();
+        let mut runtime = runtime.borrow_mut();
+        for name in raised_events {
+            let event = runtime.event_by_name(name);
+            runtime.raise_event(event);
+        }
+        runtime.update(ctx);
+        Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let ctx_ref = self.game_context.as_context_ref();
+        if let ActiveScreen::Battle(battle) = &mut self.active_screen {
+            battle.screen.draw(ctx, ctx_ref)?;
+            return graphics::present(ctx);
+        }
         self.game_context
             .overworld_screen
             .lock()
             .unwrap()
             .borrow_mut()
-            .draw(ctx, self.game_context.as_context_ref())?;
+            .draw(ctx, ctx_ref)?;
+        if let ActiveScreen::BattleTransition { elapsed, .. } = &self.active_screen {
+            draw_fade(ctx, elapsed.as_secs_f32() / BATTLE_TRANSITION.as_secs_f32())?;
+        }
         graphics::present(ctx)
     }
 
@@ -81,23 +222,25 @@ impl EventHandler<GameError> for Underkate {
             return;
         }
 
-        let ui_event = UiEvent::KeyDown { key, mods };
-        self.game_context
-            .overworld_screen
-            .lock()
-            .unwrap()
-            .borrow_mut()
-            .handle_event(ctx, self.game_context.as_context_ref(), ui_event);
+        if key == LANGUAGE_KEY {
+            let localization = self.game_context.localization.lock().unwrap();
+            let mut localization = localization.borrow_mut();
+            let next = localization.next_locale();
+            localization
+                .set_locale(next)
+                .expect("The next locale is always loaded");
+            info!(
+                "Switched the language to {}",
+                localization.current_locale().name
+            );
+            return;
+        }
+
+        self.handle_event(ctx, UiEvent::KeyDown { key, mods });
     }
 
     fn key_up_event(&mut self, ctx: &mut Context, key: KeyCode, mods: KeyMods) {
-        let ui_event = UiEvent::KeyUp { key, mods };
-        self.game_context
-            .overworld_screen
-            .lock()
-            .unwrap()
-            .borrow_mut()
-            .handle_event(ctx, self.game_context.as_context_ref(), ui_event);
+        self.handle_event(ctx, UiEvent::KeyUp { key, mods });
     }
 }
 
@@ -106,8 +249,6 @@ pub fn run() -> GameResult {
         .window_setup(WindowSetup::default().title("Underkate"))
         .build()?;
 
-    // TODO: loading overworld_screen.
-
-    let underkate = Underkate::new(&mut ctx);
+    let underkate = Underkate::new(&mut ctx)?;
     event::run(ctx, event_loop, underkate);
 }
@@ -0,0 +1,21 @@
+use ggez::audio::{SoundData, SoundSource, Source};
+use ggez::{Context, GameResult};
+
+#[derive(Debug, Clone)]
+pub struct Sound {
+    data: SoundData,
+    volume: f32,
+}
+
+impl Sound {
+    pub fn new(data: SoundData, volume: f32) -> Self {
+        Self { data, volume }
+    }
+
+    /// Plays the sound to the end, independently of any other sounds.
+    pub fn play(&self, ctx: &mut Context) -> GameResult {
+        let mut source = Source::from_data(ctx, self.data.clone())?;
+        source.set_volume(self.volume);
+        source.play_detached(ctx)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::game_context::{GameContext, GameContextRef};
+use crate::geometry::ScreenPoint;
+use crate::graphics::{draw_panel, draw_text, screen_bounds};
+use crate::screen::Screen;
+use crate::script::wait_until;
+use crate::ui_event::{is_confirm_key, UiEvent};
+use ggez::graphics::{self, Color};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use std::cell::Cell;
+use std::future::Future;
+use std::rc::Rc;
+
+const PANEL_MARGIN: f32 = 32.0;
+const TEXT_PADDING: f32 = 24.0;
+const OPTION_HEIGHT: f32 = 40.0;
+const CURSOR_WIDTH: f32 = 24.0;
+/// The space between the encounter's title and the options.
+const TITLE_HEIGHT: f32 = 64.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BattleOutcome {
+    Won,
+    Spared,
+    Fled,
+}
+
+const OPTIONS: [(BattleOutcome, &str); 3] = [
+    (BattleOutcome::Won, "battle.fight"),
+    (BattleOutcome::Spared, "battle.spare"),
+    (BattleOutcome::Fled, "battle.flee"),
+];
+
+/// The screen a battle takes place on. There are no battle mechanics yet, so the player just
+/// picks how the encounter ends.
+///
+/// The title of an encounter is the string `battle.encounters.<encounter>`.
+pub struct BattleScreen {
+    encounter: &'static str,
+    selected: usize,
+    outcome: Rc<Cell<Option<BattleOutcome>>>,
+}
+
+impl BattleScreen {
+    pub fn new(encounter: &'static str, outcome: Rc<Cell<Option<BattleOutcome>>>) -> Self {
+        outcome.set(None);
+        Self {
+            encounter,
+            selected: 0,
+            outcome,
+        }
+    }
+
+    /// `None` while the battle is in progress.
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        self.outcome.get()
+    }
+}
+
+impl Screen for BattleScreen {
+    fn draw(&mut self, ggez: &mut Context, ctx: GameContextRef<'_>) -> GameResult {
+        graphics::clear(ggez, Color::BLACK);
+        let screen = screen_bounds(ggez);
+        let rect = screen.inflate(-PANEL_MARGIN, -PANEL_MARGIN);
+        draw_panel(ggez, rect)?;
+
+        let text_rect = rect.inflate(-TEXT_PADDING, -TEXT_PADDING);
+        let localization = ctx.localization.lock().unwrap();
+        let localization = localization.borrow();
+        let title = format!("battle.encounters.{}", self.encounter);
+        draw_text(
+            ggez,
+            localization.get(&title),
+            text_rect.min,
+            text_rect.width(),
+        )?;
+        for (index, (_, label)) in OPTIONS.iter().enumerate() {
+            let top = text_rect.min.y + TITLE_HEIGHT + OPTION_HEIGHT * index as f32;
+            if index == self.selected {
+                draw_text(
+                    ggez,
+                    "*",
+                    ScreenPoint::new(text_rect.min.x, top),
+                    CURSOR_WIDTH,
+                )?;
+            }
+            draw_text(
+                ggez,
+                localization.get(label),
+                ScreenPoint::new(text_rect.min.x + CURSOR_WIDTH, top),
+                text_rect.width() - CURSOR_WIDTH,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, _ggez: &mut Context, _ctx: GameContextRef<'_>) -> GameResult {
+        Ok(())
+    }
+
+    fn handle_event(&mut self, _ggez: &mut Context, _ctx: GameContextRef<'_>, event: UiEvent) {
+        let key = match event {
+            UiEvent::KeyDown { key, .. } => key,
+            UiEvent::KeyUp { .. } => return,
+        };
+        if self.outcome().is_some() {
+            return;
+        }
+        match key {
+            KeyCode::Up => self.selected = (self.selected + OPTIONS.len() - 1) % OPTIONS.len(),
+            KeyCode::Down => self.selected = (self.selected + 1) % OPTIONS.len(),
+            key if is_confirm_key(key) => self.outcome.set(Some(OPTIONS[self.selected].0)),
+            _ => (),
+        }
+    }
+}
+
+/// Starts a battle with the encounter, resolving with its outcome once it is over.
+pub fn start(
+    context: &GameContext,
+    encounter: &'static str,
+) -> impl Future<Output = BattleOutcome> {
+    let outcome = Rc::new(Cell::new(None));
+    context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .start_battle(BattleScreen::new(encounter, Rc::clone(&outcome)), None);
+    async move {
+        wait_until(|| outcome.get().is_some()).await;
+        outcome.get().unwrap()
+    }
+}
@@ -1,9 +1,14 @@
 use crate::game_context::GameContext;
-use crate::script::{EventHandle, Runtime, Script, ScriptHandle, ScriptHandleGenerator, EventHandleGenerator};
+use crate::script::{
+    EventHandle, EventHandleGenerator, Runtime, Script, ScriptHandle, ScriptHandleGenerator,
+    WithGgezFn,
+};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::task::{Context, RawWaker, RawWakerVTable, Waker, Poll};
+use std::rc::Rc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 
 #[derive(Debug, Default)]
 struct SubscriberList {
@@ -46,7 +51,9 @@ pub struct DefaultRuntime {
     scripts: HashMap<ScriptHandle, ScriptState>,
     script_handle_generator: ScriptHandleGenerator,
     event_handle_generator: EventHandleGenerator,
-    with_ggez_queue: Vec<Box<dyn FnMut(&mut ggez::Context)>>,
+    with_ggez_queue: Vec<WithGgezFn>,
+    event_waiters: HashMap<EventHandle, Vec<Rc<Cell<bool>>>>,
+    named_events: HashMap<String, EventHandle>,
 }
 
 impl DefaultRuntime {
@@ -57,6 +64,8 @@ impl DefaultRuntime {
             script_handle_generator: ScriptHandleGenerator::new(),
             with_ggez_queue: Vec::new(),
             event_handle_generator: EventHandleGenerator::new(),
+            event_waiters: HashMap::new(),
+            named_events: HashMap::new(),
         }
     }
 
@@ -81,13 +90,17 @@ impl DefaultRuntime {
 }
 
 struct EventWaitFuture {
-    event: EventHandle,
+    raised: Rc<Cell<bool>>,
 }
 
 impl Future for EventWaitFuture {
     type Output = ();
     fn poll(self: Pin<&mut Self>, _future_ctx: &mut std::task::Context<'_>) -> Poll<()> {
-        self.event
+        if self.raised.get() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
     }
 }
 
@@ -97,6 +110,9 @@ impl Runtime for DefaultRuntime {
     }
 
     fn raise_event(&mut self, event: EventHandle) {
+        for raised in self.event_waiters.remove(&event).unwrap_or_default() {
+            raised.set(true);
+        }
         if let Some(list) = self.subscribers.remove(&event) {
             for script in list.once {
                 self.resume_script(script, Some(event));
@@ -104,26 +120,44 @@ impl Runtime for DefaultRuntime {
         }
     }
 
+    fn event_by_name(&mut self, name: &str) -> EventHandle {
+        let generator = &mut self.event_handle_generator;
+        *self
+            .named_events
+            .entry(String::from(name))
+            .or_insert_with(|| generator.gen_handle())
+    }
+
     fn wake_event(&self, script: ScriptHandle) -> Option<EventHandle> {
         self.scripts[&script].wake_event
     }
 
-    fn start_script(&mut self, context: GameContext, script: &mut dyn Script) {
+    fn start_script(&mut self, context: GameContext, script: &mut dyn Script) -> ScriptHandle {
         let handle = self.script_handle_generator.gen_handle();
         let future = script.start(handle, context);
         let state = ScriptState::from_future(future);
+        // The script is first polled on the next update, so that starting a script never
+        // re-enters whatever has started it.
         self.scripts.insert(handle, state);
-        self.resume_script(handle, None);
+        handle
+    }
+
+    fn is_running(&self, script: ScriptHandle) -> bool {
+        self.scripts.contains_key(&script)
     }
 
-    fn run_with_ggez(
-        &mut self,
-        func: Box<dyn FnMut(&mut ggez::Context)>,
-    ) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn run_with_ggez(&mut self, func: WithGgezFn) -> Pin<Box<dyn Future<Output = ()>>> {
         self.with_ggez_queue.push(func);
-        Box::pin(async {
-            
-        })
+        Box::pin(async {})
+    }
+
+    fn wait_for_event(&mut self, event: EventHandle) -> Pin<Box<dyn Future<Output = ()>>> {
+        let raised = Rc::new(Cell::new(false));
+        self.event_waiters
+            .entry(event)
+            .or_default()
+            .push(Rc::clone(&raised));
+        Box::pin(EventWaitFuture { raised })
     }
 
     fn update(&mut self, ctx: &mut ggez::Context) {
@@ -131,5 +165,11 @@ impl Runtime for DefaultRuntime {
         for mut func in queue {
             func(ctx);
         }
+
+        // Wakers are no-ops, so every script gets polled once per frame.
+        let handles: Vec<_> = self.scripts.keys().copied().collect();
+        for handle in handles {
+            self.resume_script(handle, None);
+        }
     }
 }
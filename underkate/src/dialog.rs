@@ -1,28 +1,84 @@
-use crate::script::{Script, ScriptHandle};
+pub mod dialog_box;
+pub mod history;
+
 use crate::game_context::GameContext;
+use crate::locale::Localization;
+use crate::script::{wait_until, Script, ScriptHandle};
+use dialog_box::DialogBox;
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::future::Future;
 use std::pin::Pin;
+use std::rc::Rc;
 
+#[derive(Debug, Clone)]
 pub struct Dialog {
     frames: Vec<DialogFrame>,
 }
 
-struct DialogFrame {
-    text: String,
+impl Dialog {
+    pub fn new(frames: Vec<DialogFrame>) -> Self {
+        Self { frames }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DialogFrame {
+    pub speaker_id: Option<String>,
+    pub text_id: String,
+    /// Picks the plural form of the text and is substituted for `{count}` in it.
+    pub count: Option<i64>,
+}
+
+impl DialogFrame {
+    pub fn new(speaker_id: Option<&str>, text_id: &str) -> Self {
+        Self {
+            speaker_id: speaker_id.map(String::from),
+            text_id: String::from(text_id),
+            count: None,
+        }
+    }
+
+    /// A frame whose text has plural forms.
+    pub fn with_count(speaker_id: Option<&str>, text_id: &str, count: i64) -> Self {
+        Self {
+            count: Some(count),
+            ..Self::new(speaker_id, text_id)
+        }
+    }
+
+    pub fn resolve(&self, localization: &Localization) -> String {
+        let text = match self.count {
+            Some(count) => localization.get_plural(&self.text_id, count),
+            None => Cow::Borrowed(localization.get(&self.text_id)),
+        };
+        match &self.speaker_id {
+            Some(speaker_id) => format!("{}: {}", localization.get(speaker_id), text),
+            None => String::from(text),
+        }
+    }
+}
+
+/// Shows the frames one by one in the overworld dialog box, resolving once the last one is
+/// dismissed.
+pub fn show(context: &GameContext, frames: Vec<DialogFrame>) -> impl Future<Output = ()> {
+    let finished = Rc::new(Cell::new(false));
+    context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .show_dialog(DialogBox::new(frames, Rc::clone(&finished)));
+    wait_until(move || finished.get())
 }
 
 impl Script for Dialog {
     fn start(
         &mut self,
-        script_handle: ScriptHandle,
+        _script_handle: ScriptHandle,
         context: GameContext,
     ) -> Pin<Box<dyn Future<Output = ()>>> {
-        Box::pin(async {
-            let runtime_lock = context.runtime.lock().unwrap();
-            let runtime_ref = runtime_lock.borrow();
-            runtime_ref.run_with_ggez(Box::new(|ggez| {
-                
-            }));
-        })
+        let frames = self.frames.clone();
+        Box::pin(async move { show(&context, frames).await })
     }
 }
@@ -0,0 +1,72 @@
+use super::history::DialogHistory;
+use super::DialogFrame;
+use crate::game_context::GameContextRef;
+use crate::geometry::ScreenRect;
+use crate::graphics::{draw_panel, draw_text, screen_bounds};
+use crate::ui_event::{is_confirm_key, UiEvent};
+use ggez::{Context, GameResult};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+const BOX_HEIGHT: f32 = 160.0;
+const BOX_MARGIN: f32 = 32.0;
+const TEXT_PADDING: f32 = 24.0;
+
+pub struct DialogBox {
+    frames: VecDeque<DialogFrame>,
+    finished: Rc<Cell<bool>>,
+}
+
+impl DialogBox {
+    pub fn new(frames: Vec<DialogFrame>, finished: Rc<Cell<bool>>) -> Self {
+        finished.set(frames.is_empty());
+        Self {
+            frames: frames.into(),
+            finished,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn handle_event(&mut self, ctx: GameContextRef<'_>, event: UiEvent) {
+        if let UiEvent::KeyDown { key, .. } = event {
+            if is_confirm_key(key) {
+                self.dismiss_frame(&mut ctx.dialog_history.lock().unwrap().borrow_mut());
+            }
+        }
+    }
+
+    fn dismiss_frame(&mut self, history: &mut DialogHistory) {
+        if let Some(frame) = self.frames.pop_front() {
+            history.record(frame);
+        }
+        if self.is_finished() {
+            self.finished.set(true);
+        }
+    }
+
+    pub fn draw(&self, ggez: &mut Context, ctx: GameContextRef<'_>) -> GameResult {
+        let frame = match self.frames.front() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        let screen = screen_bounds(ggez);
+        let rect = ScreenRect::new(
+            [
+                screen.min.x + BOX_MARGIN,
+                screen.max.y - BOX_MARGIN - BOX_HEIGHT,
+            ]
+            .into(),
+            [screen.max.x - BOX_MARGIN, screen.max.y - BOX_MARGIN].into(),
+        );
+        draw_panel(ggez, rect)?;
+
+        let text = frame.resolve(&ctx.localization.lock().unwrap().borrow());
+        let text_rect = rect.inflate(-TEXT_PADDING, -TEXT_PADDING);
+        draw_text(ggez, &text, text_rect.min, text_rect.width())
+    }
+}
@@ -0,0 +1,103 @@
+use super::DialogFrame;
+use crate::game_context::GameContextRef;
+use crate::geometry::ScreenPoint;
+use crate::graphics::{draw_panel, draw_text, screen_bounds};
+use crate::ui_event::{is_cancel_key, DIALOG_HISTORY_KEY};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use std::collections::VecDeque;
+
+const OVERLAY_MARGIN: f32 = 32.0;
+const TEXT_PADDING: f32 = 24.0;
+const ENTRY_HEIGHT: f32 = 72.0;
+const PAGE_SIZE: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct DialogHistory {
+    frames: VecDeque<DialogFrame>,
+    capacity: usize,
+}
+
+impl DialogHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, frame: DialogFrame) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Frames from the oldest to the most recent one.
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = &DialogFrame> + ExactSizeIterator {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DialogHistoryViewer {
+    /// How many entries the view is scrolled up from the most recent one.
+    scroll_offset: usize,
+}
+
+impl DialogHistoryViewer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `false` if the viewer has been closed.
+    pub fn handle_key_down(&mut self, key: KeyCode, history: &DialogHistory) -> bool {
+        let max_offset = history.len().saturating_sub(1);
+        match key {
+            KeyCode::Up => self.scroll_offset = (self.scroll_offset + 1).min(max_offset),
+            KeyCode::Down => self.scroll_offset = self.scroll_offset.saturating_sub(1),
+            KeyCode::PageUp => {
+                self.scroll_offset = (self.scroll_offset + PAGE_SIZE).min(max_offset)
+            }
+            KeyCode::PageDown => self.scroll_offset = self.scroll_offset.saturating_sub(PAGE_SIZE),
+            key if key == DIALOG_HISTORY_KEY || is_cancel_key(key) => return false,
+            _ => (),
+        }
+        true
+    }
+
+    pub fn draw(&self, ggez: &mut Context, ctx: GameContextRef<'_>) -> GameResult {
+        let screen = screen_bounds(ggez);
+        let rect = screen.inflate(-OVERLAY_MARGIN, -OVERLAY_MARGIN);
+        draw_panel(ggez, rect)?;
+
+        let text_rect = rect.inflate(-TEXT_PADDING, -TEXT_PADDING);
+        let visible_entries = ((text_rect.height() / ENTRY_HEIGHT).floor() as usize).max(1);
+
+        let history = ctx.dialog_history.lock().unwrap();
+        let history = history.borrow();
+        let localization = ctx.localization.lock().unwrap();
+        let localization = localization.borrow();
+
+        // The most recent visible entry is drawn at the bottom, older ones above it.
+        let visible_frames = history
+            .frames()
+            .rev()
+            .skip(self.scroll_offset)
+            .take(visible_entries);
+        for (index, frame) in visible_frames.enumerate() {
+            let top = text_rect.max.y - ENTRY_HEIGHT * (index + 1) as f32;
+            draw_text(
+                ggez,
+                &frame.resolve(&localization),
+                ScreenPoint::new(text_rect.min.x, top),
+                text_rect.width(),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Named integer values describing the progress of the game. A flag which has never been set is
+/// zero, and any non-zero flag counts as set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Flags {
+    values: HashMap<String, i64>,
+}
+
+impl Flags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> i64 {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name) != 0
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        if value == 0 {
+            self.values.remove(name);
+        } else {
+            self.values.insert(String::from(name), value);
+        }
+    }
+
+    pub fn raise(&mut self, name: &str) {
+        self.set(name, 1);
+    }
+
+    pub fn add(&mut self, name: &str, amount: i64) {
+        self.set(name, self.get(name) + amount);
+    }
+}
+
+// Conditions come from the `conditions` and `open_when` of manifests, which none of the shipped
+// rooms use yet.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlagTest {
+    Set,
+    Unset,
+    AtLeast(i64),
+    Below(i64),
+}
+
+/// A condition on a single flag, as declared in manifests.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Condition {
+    pub flag: &'static str,
+    pub test: FlagTest,
+}
+
+impl Condition {
+    pub fn holds(&self, flags: &Flags) -> bool {
+        let value = flags.get(self.flag);
+        match self.test {
+            FlagTest::Set => value != 0,
+            FlagTest::Unset => value == 0,
+            FlagTest::AtLeast(min) => value >= min,
+            FlagTest::Below(max) => value < max,
+        }
+    }
+}
+
+/// Whether every condition holds.
+pub fn all_hold(conditions: &[Condition], flags: &Flags) -> bool {
+    conditions.iter().all(|condition| condition.holds(flags))
+}
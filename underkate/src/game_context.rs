@@ -1,3 +1,7 @@
+use crate::dialog::history::DialogHistory;
+use crate::flags::Flags;
+use crate::inventory::Inventory;
+use crate::locale::Localization;
 use crate::overworld::screen::OverworldScreen;
 use crate::resources::GlobalResourceStorage;
 use crate::script::Runtime;
@@ -8,6 +12,10 @@ pub struct GameContext {
     pub overworld_screen: Arc<Mutex<RefCell<OverworldScreen>>>,
     pub global_resource_storage: Arc<GlobalResourceStorage>,
     pub runtime: Arc<Mutex<RefCell<dyn Runtime>>>,
+    pub localization: Arc<Mutex<RefCell<Localization>>>,
+    pub dialog_history: Arc<Mutex<RefCell<DialogHistory>>>,
+    pub flags: Arc<Mutex<RefCell<Flags>>>,
+    pub inventory: Arc<Mutex<RefCell<Inventory>>>,
 }
 
 impl GameContext {
@@ -16,22 +24,35 @@ impl GameContext {
             overworld_screen: &self.overworld_screen,
             global_resource_storage: &self.global_resource_storage,
             runtime: &self.runtime,
+            localization: &self.localization,
+            dialog_history: &self.dialog_history,
+            flags: &self.flags,
+            inventory: &self.inventory,
         }
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct GameContextRef<'a> {
     pub overworld_screen: &'a Arc<Mutex<RefCell<OverworldScreen>>>,
     pub global_resource_storage: &'a Arc<GlobalResourceStorage>,
     pub runtime: &'a Arc<Mutex<RefCell<dyn Runtime>>>,
+    pub localization: &'a Arc<Mutex<RefCell<Localization>>>,
+    pub dialog_history: &'a Arc<Mutex<RefCell<DialogHistory>>>,
+    pub flags: &'a Arc<Mutex<RefCell<Flags>>>,
+    pub inventory: &'a Arc<Mutex<RefCell<Inventory>>>,
 }
 
 impl GameContextRef<'_> {
-    pub fn to_owned(&self) -> GameContext {
+    pub fn to_owned(self) -> GameContext {
         GameContext {
             overworld_screen: Arc::clone(self.overworld_screen),
             global_resource_storage: Arc::clone(self.global_resource_storage),
             runtime: Arc::clone(self.runtime),
+            localization: Arc::clone(self.localization),
+            dialog_history: Arc::clone(self.dialog_history),
+            flags: Arc::clone(self.flags),
+            inventory: Arc::clone(self.inventory),
         }
     }
 }
@@ -1,4 +1,5 @@
 use euclid::{Box2D, Length, Point2D, Size2D, Vector2D};
+use ggez::graphics::Rect;
 use ggez::mint;
 
 pub mod unit {
@@ -46,3 +47,9 @@ macro_rules! make_on_screen_impl {
 }
 
 make_on_screen_impl!(ScreenPoint => Point2);
+
+impl OnScreen<Rect> for ScreenRect<f32> {
+    fn on_screen(&self) -> Rect {
+        Rect::new(self.min.x, self.min.y, self.width(), self.height())
+    }
+}
@@ -1,8 +1,44 @@
 pub mod texture;
 
-use crate::geometry::ScreenPoint;
+use crate::geometry::{OnScreen, ScreenPoint, ScreenRect};
+use ggez::graphics::{self, Align, Color, DrawMode, DrawParam, Mesh, Text};
 use ggez::{Context, GameResult};
 
+const PANEL_BORDER_WIDTH: f32 = 6.0;
+
 pub trait Draw {
     fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult;
 }
+
+pub fn screen_bounds(ctx: &Context) -> ScreenRect<f32> {
+    let rect = graphics::screen_coordinates(ctx);
+    ScreenRect::new(
+        [rect.x, rect.y].into(),
+        [rect.x + rect.w, rect.y + rect.h].into(),
+    )
+}
+
+/// Draws an Undertale-style box: black fill with a white border.
+pub fn draw_panel(ctx: &mut Context, rect: ScreenRect<f32>) -> GameResult {
+    let fill = Mesh::new_rectangle(ctx, DrawMode::fill(), rect.on_screen(), Color::BLACK)?;
+    graphics::draw(ctx, &fill, DrawParam::new())?;
+    let border = Mesh::new_rectangle(
+        ctx,
+        DrawMode::stroke(PANEL_BORDER_WIDTH),
+        rect.inflate(-PANEL_BORDER_WIDTH / 2.0, -PANEL_BORDER_WIDTH / 2.0)
+            .on_screen(),
+        Color::WHITE,
+    )?;
+    graphics::draw(ctx, &border, DrawParam::new())
+}
+
+pub fn draw_text(
+    ctx: &mut Context,
+    text: &str,
+    top_left: ScreenPoint<f32>,
+    max_width: f32,
+) -> GameResult {
+    let mut text = Text::new(text);
+    text.set_bounds([max_width, f32::INFINITY], Align::Left);
+    graphics::draw(ctx, &text, DrawParam::new().dest(top_left.on_screen()))
+}
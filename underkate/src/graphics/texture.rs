@@ -1,6 +1,6 @@
 use super::Draw;
 use crate::geometry::{OnScreen, ScreenDimensions, ScreenPoint};
-use ggez::graphics::{self, DrawParam, Image};
+use ggez::graphics::{self, Color, DrawParam, Image};
 use ggez::{Context, GameResult};
 use std::time::{Duration, Instant};
 
@@ -18,6 +18,7 @@ enum TextureKind {
 #[derive(Debug, Clone, Copy)]
 struct DrawParams {
     inhibit_animation: bool,
+    opacity: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -69,10 +70,48 @@ impl Texture {
             center_at,
             &DrawParams {
                 inhibit_animation: true,
+                opacity: 1.0,
             },
         )
     }
 
+    pub fn draw_with_opacity(
+        &self,
+        ctx: &mut Context,
+        center_at: ScreenPoint<f32>,
+        opacity: f32,
+    ) -> GameResult {
+        self.generic_draw(
+            ctx,
+            center_at,
+            &DrawParams {
+                inhibit_animation: false,
+                opacity,
+            },
+        )
+    }
+
+    /// How long it takes to show every frame once. Zero for static textures.
+    pub fn animation_duration(&self) -> Duration {
+        match &self.kind {
+            TextureKind::Static(_) => Duration::ZERO,
+            TextureKind::Animated {
+                frames,
+                frame_interval,
+                ..
+            } => *frame_interval * frames.len() as u32,
+        }
+    }
+
+    /// A copy of the texture whose animation starts from the first frame now.
+    pub fn restarted(&self) -> Texture {
+        let mut texture = self.clone();
+        if let TextureKind::Animated { start_instant, .. } = &mut texture.kind {
+            *start_instant = Instant::now();
+        }
+        texture
+    }
+
     pub fn dimensions(&self) -> ScreenDimensions<f32> {
         match &self.kind {
             TextureKind::Static(image) => image_to_dimensions(image).into(),
@@ -84,7 +123,7 @@ impl Texture {
 
     fn image_for_now(&self, params: &DrawParams) -> &Image {
         match &self.kind {
-            TextureKind::Static(ref image) => &image,
+            TextureKind::Static(image) => image,
             TextureKind::Animated { frames, .. } if params.inhibit_animation => &frames[0],
             TextureKind::Animated {
                 frames,
@@ -115,7 +154,8 @@ impl Texture {
             DrawParam::new()
                 .dest(center_at.on_screen())
                 .scale(scale_vector)
-                .offset([0.5, 0.5]),
+                .offset([0.5, 0.5])
+                .color(Color::new(1.0, 1.0, 1.0, params.opacity)),
         )
     }
 }
@@ -127,6 +167,7 @@ impl Draw for Texture {
             center_at,
             &DrawParams {
                 inhibit_animation: false,
+                opacity: 1.0,
             },
         )
     }
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// How many items the player can carry at once.
+pub const INVENTORY_CAPACITY: usize = 8;
+
+/// The items the player carries, in the order they were picked up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Inventory {
+    items: Vec<String>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(String::as_str)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= INVENTORY_CAPACITY
+    }
+
+    /// Returns `false` if there is no room for the item.
+    pub fn add(&mut self, item: &str) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.items.push(String::from(item));
+        true
+    }
+}
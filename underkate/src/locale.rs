@@ -0,0 +1,155 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PluralCategory {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PluralRule {
+    /// English-like: "1 item", "2 items".
+    OneOther,
+    /// Russian-like: "1 предмет", "2 предмета", "5 предметов".
+    EastSlavic,
+}
+
+impl PluralRule {
+    pub fn category(&self, count: i64) -> PluralCategory {
+        let n = count.unsigned_abs();
+        match self {
+            Self::OneOther if n == 1 => PluralCategory::One,
+            Self::OneOther => PluralCategory::Other,
+            Self::EastSlavic => match (n % 10, n % 100) {
+                (1, rem100) if rem100 != 11 => PluralCategory::One,
+                (2..=4, rem100) if !(12..=14).contains(&rem100) => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct PluralForms {
+    pub one: Option<&'static str>,
+    pub few: Option<&'static str>,
+    pub many: Option<&'static str>,
+    pub other: &'static str,
+}
+
+impl PluralForms {
+    pub fn get(&self, category: PluralCategory) -> &'static str {
+        let form = match category {
+            PluralCategory::One => self.one,
+            PluralCategory::Few => self.few,
+            PluralCategory::Many => self.many,
+            PluralCategory::Other => None,
+        };
+        form.unwrap_or(self.other)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum LocalizedString {
+    Simple(&'static str),
+    Plural(PluralForms),
+}
+
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub fallback: Option<&'static str>,
+    pub plural_rule: PluralRule,
+    pub strings: HashMap<&'static str, LocalizedString>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct LocaleDoesNotExist<'a> {
+    pub id: &'a str,
+}
+
+impl Display for LocaleDoesNotExist<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "Locale with id `{}` does not exist", self.id)
+    }
+}
+
+impl Error for LocaleDoesNotExist<'_> {}
+
+pub struct Localization {
+    locales: HashMap<&'static str, Locale>,
+    current: &'static str,
+}
+
+impl Localization {
+    pub fn new(locales: Vec<Locale>, default: &'static str) -> Self {
+        let locales: HashMap<_, _> = locales.into_iter().map(|l| (l.id, l)).collect();
+        if !locales.contains_key(default) {
+            panic!("Default locale `{}` is not loaded", default);
+        }
+        Self {
+            locales,
+            current: default,
+        }
+    }
+
+    pub fn current_locale(&self) -> &Locale {
+        &self.locales[self.current]
+    }
+
+    pub fn set_locale<'a>(&mut self, id: &'a str) -> Result<(), LocaleDoesNotExist<'a>> {
+        let (&id, _) = self
+            .locales
+            .get_key_value(id)
+            .ok_or(LocaleDoesNotExist { id })?;
+        self.current = id;
+        Ok(())
+    }
+
+    /// The locale following the current one in the order of IDs, wrapping around.
+    pub fn next_locale(&self) -> &'static str {
+        let mut ids: Vec<_> = self.locales.keys().copied().collect();
+        ids.sort_unstable();
+        let index = ids.iter().position(|&id| id == self.current).unwrap();
+        ids[(index + 1) % ids.len()]
+    }
+
+    // Looks the key up in the current locale and then along its fallback chain.
+    fn lookup(&self, key: &str) -> Option<(&Locale, LocalizedString)> {
+        let mut maybe_locale = Some(self.current_locale());
+        while let Some(locale) = maybe_locale {
+            if let Some(&string) = locale.strings.get(key) {
+                return Some((locale, string));
+            }
+            maybe_locale = locale.fallback.map(|id| &self.locales[id]);
+        }
+        None
+    }
+
+    /// Resolves a string ID. Unknown IDs resolve to themselves, so that they are easy to spot.
+    pub fn get<'a>(&self, key: &'a str) -> &'a str {
+        match self.lookup(key) {
+            Some((_, LocalizedString::Simple(text))) => text,
+            Some((_, LocalizedString::Plural(forms))) => forms.other,
+            None => key,
+        }
+    }
+
+    /// Resolves a string ID with plural forms, substituting `{count}` in the chosen form.
+    pub fn get_plural<'a>(&self, key: &'a str, count: i64) -> Cow<'a, str> {
+        let form = match self.lookup(key) {
+            Some((locale, LocalizedString::Plural(forms))) => {
+                forms.get(locale.plural_rule.category(count))
+            }
+            Some((_, LocalizedString::Simple(text))) => text,
+            None => return Cow::Borrowed(key),
+        };
+        Cow::Owned(form.replace("{count}", &count.to_string()))
+    }
+}
@@ -1,17 +1,24 @@
 mod app;
+mod audio;
+mod battle;
 mod default_runtime;
 mod dialog;
+mod flags;
 mod game_context;
 mod geometry;
 mod graphics;
 mod handle;
+mod inventory;
+mod locale;
 mod overworld;
 mod resources;
+mod save;
 mod screen;
 mod script;
 mod ui_event;
 
 fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
     match app::run() {
         Ok(()) => (),
         Err(e) => {
@@ -1,12 +1,29 @@
+pub mod camera;
+pub mod choreography;
 pub mod collide;
+pub mod draw_order;
+pub mod encounter;
+pub mod enemy;
+pub mod exit;
+pub mod follower;
 pub mod geometry;
 pub mod hitbox;
+pub mod interaction;
+pub mod layer;
 pub mod move_trait;
+pub mod movement;
 pub mod multiside;
 pub mod multiwalk;
+pub mod navigation;
+pub mod npc;
 pub mod pass_map;
 pub mod passability_checker;
+pub mod pickup;
 pub mod player;
+pub mod puzzle;
 pub mod room;
+pub mod room_state;
 pub mod screen;
+pub mod sign;
+pub mod trigger;
 pub mod walk;
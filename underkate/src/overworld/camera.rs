@@ -0,0 +1,147 @@
+use super::geometry::TranslationContext;
+use super::move_trait::Position;
+use crate::game_context::GameContext;
+use crate::geometry::{OverworldDimensions, OverworldRect, OverworldVector};
+use crate::script::wait_until;
+use std::cell::Cell;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+const DEAD_ZONE_HALF_WIDTH: f32 = 48.0;
+const DEAD_ZONE_HALF_HEIGHT: f32 = 32.0;
+
+struct Pan {
+    target: Position,
+    speed: f32,
+    finished: Rc<Cell<bool>>,
+}
+
+enum CameraMode {
+    FollowPlayer,
+    /// Scripts control the camera; it stays in place unless a pan is in progress.
+    Scripted(Option<Pan>),
+}
+
+pub struct Camera {
+    center: Position,
+    viewport: OverworldDimensions<f32>,
+    mode: CameraMode,
+}
+
+impl Camera {
+    pub fn new(center: Position) -> Self {
+        Self {
+            center,
+            viewport: OverworldDimensions::zero(),
+            mode: CameraMode::FollowPlayer,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        player_position: Position,
+        bounds: OverworldRect<f32>,
+        viewport: OverworldDimensions<f32>,
+        time_slice: Duration,
+    ) {
+        self.viewport = viewport;
+        match &mut self.mode {
+            CameraMode::FollowPlayer => {
+                self.center = follow(self.center, player_position);
+            }
+            CameraMode::Scripted(maybe_pan) => {
+                if let Some(pan) = maybe_pan {
+                    let target = clamp_center(pan.target, bounds, viewport);
+                    let max_step = pan.speed * time_slice.as_secs_f32();
+                    let remaining = target - self.center;
+                    if remaining.length() <= max_step {
+                        self.center = target;
+                        pan.finished.set(true);
+                        *maybe_pan = None;
+                    } else {
+                        self.center += remaining.normalize() * max_step;
+                    }
+                }
+            }
+        }
+        self.center = clamp_center(self.center, bounds, viewport);
+    }
+
+    /// Takes the camera away from the player and moves it towards `target` at `speed` units per
+    /// second. The returned flag is set once the camera arrives. The camera lets go of the flag
+    /// without setting it if the pan is abandoned, e.g. by leaving the room. The camera stays
+    /// where the pan ends until `follow_player` is called.
+    pub fn pan_to(&mut self, target: Position, speed: f32) -> Rc<Cell<bool>> {
+        let finished = Rc::new(Cell::new(false));
+        self.mode = CameraMode::Scripted(Some(Pan {
+            target,
+            speed,
+            finished: Rc::clone(&finished),
+        }));
+        finished
+    }
+
+    pub fn follow_player(&mut self) {
+        self.mode = CameraMode::FollowPlayer;
+    }
+
+    pub fn translation_context(&self) -> TranslationContext {
+        let half_viewport = self.viewport.to_vector() * 0.5;
+        TranslationContext::new((self.center - half_viewport).round())
+    }
+}
+
+fn follow(center: Position, target: Position) -> Position {
+    let dead_zone = OverworldRect::new(
+        center - OverworldVector::new(DEAD_ZONE_HALF_WIDTH, DEAD_ZONE_HALF_HEIGHT),
+        center + OverworldVector::new(DEAD_ZONE_HALF_WIDTH, DEAD_ZONE_HALF_HEIGHT),
+    );
+    let clamped_target = target.clamp(dead_zone.min, dead_zone.max);
+    center + (target - clamped_target)
+}
+
+// Keeps the view inside `bounds`, or centered on them if they are smaller than the view.
+fn clamp_center(
+    center: Position,
+    bounds: OverworldRect<f32>,
+    viewport: OverworldDimensions<f32>,
+) -> Position {
+    let clamp_axis = |value: f32, min: f32, max: f32, view_size: f32| {
+        if max - min <= view_size {
+            (min + max) / 2.0
+        } else {
+            value.max(min + view_size / 2.0).min(max - view_size / 2.0)
+        }
+    };
+    Position::new(
+        clamp_axis(center.x, bounds.min.x, bounds.max.x, viewport.width),
+        clamp_axis(center.y, bounds.min.y, bounds.max.y, viewport.height),
+    )
+}
+
+/// Resolves once the camera arrives, or once the pan is abandoned: another pan or
+/// `follow_player` replaces it, or the room (and with it the camera) changes. The camera keeps
+/// looking at `target` afterwards, so scripts must call `follow_player` when they are done.
+pub fn pan_to(context: &GameContext, target: Position, speed: f32) -> impl Future<Output = ()> {
+    let finished = context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .room_mut()
+        .camera_mut()
+        .pan_to(target, speed);
+    wait_until(move || finished.get() || Rc::strong_count(&finished) == 1)
+}
+
+pub fn follow_player(context: &GameContext) {
+    context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .room_mut()
+        .camera_mut()
+        .follow_player();
+}
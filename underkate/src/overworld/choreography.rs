@@ -0,0 +1,170 @@
+use super::move_trait::{Direction, Position};
+use super::multiside::OneShotAnimation;
+use super::navigation::{self, NavigationError};
+use super::room::{EntityId, Room};
+use crate::game_context::GameContext;
+use crate::graphics::texture::Texture;
+use crate::resources::ResourceStorageCloneExt;
+use crate::script::wait_until;
+use std::time::{Duration, Instant};
+
+/// A texture shown above an entity's head for a while, such as an exclamation mark.
+#[derive(Debug, Clone)]
+pub struct Emote {
+    pub entity: EntityId,
+    pub texture: Texture,
+    pub ends_at: Instant,
+}
+
+impl Emote {
+    pub fn is_finished(&self) -> bool {
+        Instant::now() >= self.ends_at
+    }
+}
+
+/// Takes the controls away from the player while alive. Cutscenes can be nested.
+pub struct Cutscene<'a> {
+    context: &'a GameContext,
+}
+
+impl<'a> Cutscene<'a> {
+    pub fn begin(context: &'a GameContext) -> Self {
+        context
+            .overworld_screen
+            .lock()
+            .unwrap()
+            .borrow_mut()
+            .begin_cutscene();
+        Self { context }
+    }
+}
+
+impl Drop for Cutscene<'_> {
+    fn drop(&mut self) {
+        self.context
+            .overworld_screen
+            .lock()
+            .unwrap()
+            .borrow_mut()
+            .end_cutscene();
+    }
+}
+
+fn with_room<R>(context: &GameContext, func: impl FnOnce(&mut Room) -> R) -> R {
+    let screen = context.overworld_screen.lock().unwrap();
+    let mut screen = screen.borrow_mut();
+    func(screen.room_mut())
+}
+
+pub fn entity_position(
+    context: &GameContext,
+    entity: &EntityId,
+) -> Result<Position, NavigationError> {
+    with_room(context, |room| {
+        room.entity(entity)
+            .map(|agent| agent.position())
+            .ok_or_else(|| NavigationError::NoSuchEntity(entity.clone()))
+    })
+}
+
+/// Scales how fast the entity walks, e.g. to have someone hurry over. Returns the previous
+/// multiplier, so that it can be restored afterwards.
+pub fn set_speed_multiplier(
+    context: &GameContext,
+    entity: &EntityId,
+    multiplier: f32,
+) -> Result<f32, NavigationError> {
+    with_room(context, |room| {
+        let movement = room
+            .entity_mut(entity)
+            .ok_or_else(|| NavigationError::NoSuchEntity(entity.clone()))?
+            .movement_mut();
+        let previous = movement.speed_multiplier();
+        movement.set_speed_multiplier(multiplier);
+        Ok(previous)
+    })
+}
+
+/// Walks the entity through the waypoints in straight lines at the given speed, ignoring its
+/// movement profile.
+pub async fn walk_path(
+    context: &GameContext,
+    entity: EntityId,
+    waypoints: Vec<Position>,
+    speed: f32,
+) -> Result<(), NavigationError> {
+    navigation::follow_path(context, entity, waypoints, Some(speed)).await
+}
+
+pub async fn face(
+    context: &GameContext,
+    entity: EntityId,
+    direction: Direction,
+) -> Result<(), NavigationError> {
+    with_room(context, |room| {
+        let agent = room
+            .entity_mut(&entity)
+            .ok_or_else(|| NavigationError::NoSuchEntity(entity.clone()))?;
+        agent.set_direction(direction);
+        Ok(())
+    })
+}
+
+/// Turns the entity towards another one.
+pub async fn face_entity(
+    context: &GameContext,
+    entity: EntityId,
+    target: EntityId,
+) -> Result<(), NavigationError> {
+    let from = entity_position(context, &entity)?;
+    let to = entity_position(context, &target)?;
+    if from == to {
+        return Ok(());
+    }
+    face(context, entity, Direction::from_vector(to - from)).await
+}
+
+/// Plays the animated texture once in place of the entity's usual textures, resolving when it
+/// ends. The player can't move until then.
+pub async fn animate(
+    context: &GameContext,
+    entity: EntityId,
+    texture_path: &str,
+) -> Result<(), NavigationError> {
+    let texture: Texture = context.global_resource_storage.get_cloned(texture_path);
+    let _cutscene = (entity == EntityId::Player).then(|| Cutscene::begin(context));
+    with_room(context, |room| {
+        let agent = room
+            .entity_mut(&entity)
+            .ok_or_else(|| NavigationError::NoSuchEntity(entity.clone()))?;
+        agent.set_one_shot_animation(Some(OneShotAnimation::start(&texture)));
+        Ok(())
+    })?;
+    let ends_at = Instant::now() + texture.animation_duration();
+    wait_until(|| Instant::now() >= ends_at).await;
+    with_room(context, |room| {
+        if let Some(agent) = room.entity_mut(&entity) {
+            agent.set_one_shot_animation(None);
+        }
+    });
+    Ok(())
+}
+
+/// Shows the texture above the entity for the given time.
+pub async fn emote(
+    context: &GameContext,
+    entity: EntityId,
+    texture_path: &str,
+    duration: Duration,
+) -> Result<(), NavigationError> {
+    let emote = Emote {
+        entity: entity.clone(),
+        texture: context.global_resource_storage.get_cloned(texture_path),
+        ends_at: Instant::now() + duration,
+    };
+    let ends_at = emote.ends_at;
+    entity_position(context, &entity)?;
+    with_room(context, |room| room.show_emote(emote));
+    wait_until(|| Instant::now() >= ends_at).await;
+    Ok(())
+}
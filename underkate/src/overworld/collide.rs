@@ -1,33 +1,190 @@
 use super::hitbox::Hitbox;
 use super::move_trait::Position;
 use super::passability_checker::PassabilityCheck;
+use crate::geometry::OverworldVector;
+
+/// The longest distance an entity moves between two collision checks. Walls thinner than this
+/// can be walked through, so it should not exceed a pass map pixel.
+const SWEEP_STEP: f32 = 1.0;
+
+/// How precisely an entity is moved up to an obstacle: the remaining gap is at most
+/// `SWEEP_STEP / 2^CONTACT_SEARCH_ITERATIONS`.
+const CONTACT_SEARCH_ITERATIONS: usize = 6;
+
+/// How entities get pushed around corners they bump into, e.g. when walking into a doorway
+/// slightly off its center.
+#[derive(Debug, Copy, Clone)]
+pub struct CornerNudge {
+    /// The farthest an entity can be from the free side of an obstacle to be moved around it.
+    /// Zero disables nudging.
+    pub max_distance: f32,
+}
+
+impl Default for CornerNudge {
+    fn default() -> Self {
+        Self { max_distance: 8.0 }
+    }
+}
+
+const NORMAL_PROBE_COUNT: usize = 16;
+const NORMAL_PROBE_DISTANCE: f32 = 1.5;
+const MIN_SLIDE_FRACTION: f32 = 0.2;
 
 pub trait Collide {
     fn can_move_to(&self, position: Position, pass: &impl PassabilityCheck) -> bool;
 
+    /// Moves along `delta` as far as possible without colliding.
+    fn advance_until_contact(
+        &self,
+        from: Position,
+        delta: OverworldVector<f32>,
+        pass: &impl PassabilityCheck,
+    ) -> Position {
+        if self.can_move_to(from + delta, pass) {
+            return from + delta;
+        }
+        let mut passable_fraction = 0.0;
+        let mut blocked_fraction = 1.0;
+        for _ in 0..CONTACT_SEARCH_ITERATIONS {
+            let middle = (passable_fraction + blocked_fraction) / 2.0;
+            if self.can_move_to(from + delta * middle, pass) {
+                passable_fraction = middle;
+            } else {
+                blocked_fraction = middle;
+            }
+        }
+        from + delta * passable_fraction
+    }
+
+    /// Estimates the direction pointing away from the obstacles around `position` by probing
+    /// positions on a small circle. Returns `None` if nothing is blocked, or if obstacles
+    /// surround the entity evenly.
+    fn estimate_wall_normal(
+        &self,
+        position: Position,
+        pass: &impl PassabilityCheck,
+    ) -> Option<OverworldVector<f32>> {
+        let towards_obstacles: OverworldVector<f32> = (0..NORMAL_PROBE_COUNT)
+            .map(|i| {
+                let angle = i as f32 / NORMAL_PROBE_COUNT as f32 * std::f32::consts::TAU;
+                OverworldVector::new(angle.cos(), angle.sin())
+            })
+            .filter(|&direction| {
+                !self.can_move_to(position + direction * NORMAL_PROBE_DISTANCE, pass)
+            })
+            .fold(OverworldVector::zero(), |sum, direction| sum + direction);
+        if towards_obstacles.square_length() < 0.5 {
+            None
+        } else {
+            Some(-towards_obstacles.normalize())
+        }
+    }
+
+    /// Moves up to the wall, then along it by the part of `step` which is parallel to the wall.
+    fn slide_along_wall(
+        &self,
+        position: Position,
+        step: OverworldVector<f32>,
+        pass: &impl PassabilityCheck,
+    ) -> Option<Position> {
+        let contact = self.advance_until_contact(position, step, pass);
+        let normal = self.estimate_wall_normal(contact, pass)?;
+        let into_wall = step.dot(normal);
+        if into_wall >= 0.0 {
+            return None;
+        }
+        let along_wall = step - normal * into_wall;
+        // Walking straight into a wall; leave it to corner nudging.
+        if along_wall.length() < step.length() * MIN_SLIDE_FRACTION {
+            return None;
+        }
+        // Only the part of the step left after reaching the wall is redirected along it.
+        let remaining = position + step - contact;
+        let along_wall = remaining - normal * remaining.dot(normal);
+        let next = self.advance_until_contact(contact, along_wall, pass);
+        if next == position {
+            None
+        } else {
+            Some(next)
+        }
+    }
+
+    /// If the way ahead is free a little to the side, moves sideways towards it. The closer side
+    /// wins.
+    fn nudge_around_corner(
+        &self,
+        position: Position,
+        step: OverworldVector<f32>,
+        nudge: CornerNudge,
+        pass: &impl PassabilityCheck,
+    ) -> Option<Position> {
+        let step_length = step.length();
+        if step_length == 0.0 {
+            return None;
+        }
+        let sideways = OverworldVector::new(-step.y, step.x) / step_length;
+        let max_offset = nudge.max_distance.floor() as usize;
+        for offset in 1..=max_offset {
+            for &side in &[sideways, -sideways] {
+                let shifted = position + side * offset as f32;
+                let path_clear = (1..=offset)
+                    .all(|distance| self.can_move_to(position + side * distance as f32, pass));
+                if path_clear && self.can_move_to(shifted + step, pass) {
+                    return Some(position + side * step_length.min(offset as f32));
+                }
+            }
+        }
+        None
+    }
+
+    // Sweeps the movement in short steps, so that a long move (e.g. after a lag spike) cannot
+    // skip over thin walls. A blocked step slides along the wall, or gets nudged around a corner,
+    // or, failing that, is resolved axis by axis, which leaves the entity flush against the wall.
     fn find_passable_position(
         &self,
         orig_position: Position,
         assumed_new_position: Position,
+        nudge: CornerNudge,
         pass: &impl PassabilityCheck,
     ) -> Option<Position> {
-        if self.can_move_to(assumed_new_position, pass) {
-            return Some(assumed_new_position);
-        }
+        let movement = assumed_new_position - orig_position;
+        let step_count = (movement.length() / SWEEP_STEP).ceil().max(1.0) as usize;
+        let step = movement / step_count as f32;
 
-        // Try moving only horizontally/vertically.
-        let assumed_new_horizontal_position =
-            Position::new(assumed_new_position.x, orig_position.y);
-        if self.can_move_to(assumed_new_horizontal_position, pass) {
-            return Some(assumed_new_horizontal_position);
-        }
+        let mut position = orig_position;
+        for _ in 0..step_count {
+            if self.can_move_to(position + step, pass) {
+                position += step;
+                continue;
+            }
 
-        let assumed_new_vertical_position = Position::new(orig_position.x, assumed_new_position.y);
-        if self.can_move_to(assumed_new_vertical_position, pass) {
-            return Some(assumed_new_vertical_position);
+            if let Some(next) = self
+                .slide_along_wall(position, step, pass)
+                .or_else(|| self.nudge_around_corner(position, step, nudge, pass))
+            {
+                position = next;
+                continue;
+            }
+
+            // Try moving only horizontally/vertically.
+            let after_horizontal =
+                self.advance_until_contact(position, OverworldVector::new(step.x, 0.0), pass);
+            let after_vertical = self.advance_until_contact(
+                after_horizontal,
+                OverworldVector::new(0.0, step.y),
+                pass,
+            );
+            if after_vertical == position {
+                break;
+            }
+            position = after_vertical;
         }
 
-        None
+        if position == orig_position {
+            None
+        } else {
+            Some(position)
+        }
     }
 }
 
@@ -36,3 +193,153 @@ impl<T: Hitbox> Collide for T {
         pass.can_pass(&self.hitbox_at(position))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::OverworldRect;
+
+    struct Walls<F: Fn(&OverworldRect<f32>) -> bool> {
+        collides: F,
+    }
+
+    impl<F: Fn(&OverworldRect<f32>) -> bool> PassabilityCheck for Walls<F> {
+        fn can_pass(&self, player_hitbox: &OverworldRect<f32>) -> bool {
+            !(self.collides)(player_hitbox)
+        }
+    }
+
+    fn rect(x1: f32, y1: f32, x2: f32, y2: f32) -> OverworldRect<f32> {
+        OverworldRect::new([x1, y1].into(), [x2, y2].into())
+    }
+
+    fn walls(walls: Vec<OverworldRect<f32>>) -> Walls<impl Fn(&OverworldRect<f32>) -> bool> {
+        Walls {
+            collides: move |hitbox: &OverworldRect<f32>| {
+                walls.iter().any(|wall| wall.intersects(hitbox))
+            },
+        }
+    }
+
+    struct TestAgent;
+
+    impl Hitbox for TestAgent {
+        fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+            rect(-4.0, -4.0, 4.0, 4.0)
+        }
+    }
+
+    fn move_by(
+        from: Position,
+        dx: f32,
+        dy: f32,
+        nudge: CornerNudge,
+        pass: &impl PassabilityCheck,
+    ) -> Option<Position> {
+        TestAgent.find_passable_position(from, from + OverworldVector::new(dx, dy), nudge, pass)
+    }
+
+    fn assert_flush(coordinate: f32, wall_edge: f32) {
+        let max_gap = SWEEP_STEP / (1 << CONTACT_SEARCH_ITERATIONS) as f32;
+        assert!(
+            coordinate <= wall_edge && coordinate >= wall_edge - max_gap,
+            "{} is not flush against {}",
+            coordinate,
+            wall_edge,
+        );
+    }
+
+    #[test]
+    fn long_moves_do_not_skip_thin_walls() {
+        let pass = walls(vec![rect(0.0, 50.0, 200.0, 51.0)]);
+        let end = move_by(
+            Position::new(20.0, 40.0),
+            0.0,
+            40.0,
+            CornerNudge::default(),
+            &pass,
+        )
+        .unwrap();
+        assert_eq!(end.x, 20.0);
+        assert_flush(end.y + 4.0, 50.0);
+    }
+
+    #[test]
+    fn slides_along_axis_aligned_wall() {
+        let pass = walls(vec![rect(0.0, 50.0, 200.0, 60.0)]);
+        let end = move_by(
+            Position::new(20.0, 40.0),
+            10.0,
+            10.0,
+            CornerNudge::default(),
+            &pass,
+        )
+        .unwrap();
+        assert!((end.x - 30.0).abs() < 0.5, "did not slide: {:?}", end);
+        assert_flush(end.y + 4.0, 50.0);
+    }
+
+    #[test]
+    fn slides_along_diagonal_wall() {
+        // Everything below the line x + y = 200 is solid.
+        let pass = Walls {
+            collides: |hitbox: &OverworldRect<f32>| hitbox.max.x + hitbox.max.y > 200.0,
+        };
+        let start = Position::new(90.0, 90.0);
+        let end = move_by(start, 10.0, 0.0, CornerNudge::default(), &pass).unwrap();
+        assert!(TestAgent.can_move_to(end, &pass));
+        let along_wall = OverworldVector::new(1.0, -1.0).normalize();
+        assert!(
+            (end - start).dot(along_wall) > 4.0,
+            "did not slide: {:?}",
+            end
+        );
+    }
+
+    #[test]
+    fn stops_in_concave_corner() {
+        let pass = walls(vec![
+            rect(100.0, 0.0, 110.0, 110.0),
+            rect(0.0, 100.0, 110.0, 110.0),
+        ]);
+        let end = move_by(
+            Position::new(90.0, 90.0),
+            10.0,
+            10.0,
+            CornerNudge::default(),
+            &pass,
+        )
+        .unwrap();
+        assert_flush(end.x + 4.0, 100.0);
+        assert_flush(end.y + 4.0, 100.0);
+        assert_eq!(
+            move_by(end, 10.0, 10.0, CornerNudge::default(), &pass),
+            None
+        );
+    }
+
+    #[test]
+    fn nudges_around_corner_within_max_distance() {
+        // The agent overlaps the end of the wall by 3 units.
+        let pass = walls(vec![rect(0.0, 50.0, 100.0, 60.0)]);
+        let end = move_by(
+            Position::new(101.0, 40.0),
+            0.0,
+            30.0,
+            CornerNudge::default(),
+            &pass,
+        )
+        .unwrap();
+        assert!(end.x >= 104.0, "was not nudged: {:?}", end);
+        assert!(end.y > 60.0, "did not get past the wall: {:?}", end);
+    }
+
+    #[test]
+    fn does_not_nudge_beyond_max_distance() {
+        let pass = walls(vec![rect(0.0, 50.0, 100.0, 60.0)]);
+        let nudge = CornerNudge { max_distance: 4.0 };
+        let end = move_by(Position::new(98.0, 40.0), 0.0, 30.0, nudge, &pass).unwrap();
+        assert_eq!(end.x, 98.0);
+        assert_flush(end.y + 4.0, 50.0);
+    }
+}
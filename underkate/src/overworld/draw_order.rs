@@ -0,0 +1,37 @@
+use super::hitbox::Hitbox;
+use super::move_trait::Move;
+use crate::graphics::Draw;
+use std::cmp::Ordering;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Default)]
+pub enum DrawLayer {
+    /// Always drawn below sorted entities, e.g. rugs or floor switches.
+    Below,
+    #[default]
+    Sorted,
+    /// Always drawn above sorted entities, e.g. ceiling lamps.
+    // Only set from manifests, and none of the shipped rooms has anything above the rest yet.
+    #[allow(dead_code)]
+    Above,
+}
+
+pub trait YSorted: Draw + Move + Hitbox {
+    fn draw_layer(&self) -> DrawLayer {
+        DrawLayer::Sorted
+    }
+
+    /// Entities are drawn in the order of their sort anchors, so that ones closer to the bottom
+    /// of the screen cover the ones behind them. Defaults to the bottom edge of the hitbox, i.e.
+    /// the feet.
+    fn sort_anchor(&self) -> f32 {
+        self.hitbox_at(self.position()).max.y
+    }
+}
+
+pub fn draw_order(a: &dyn YSorted, b: &dyn YSorted) -> Ordering {
+    a.draw_layer().cmp(&b.draw_layer()).then_with(|| {
+        a.sort_anchor()
+            .partial_cmp(&b.sort_anchor())
+            .unwrap_or(Ordering::Equal)
+    })
+}
@@ -0,0 +1,179 @@
+use super::trigger::Shape;
+use crate::flags::{self, Condition, Flags};
+use crate::geometry::OverworldRect;
+use oorandom::Rand32;
+
+#[derive(Debug, Copy, Clone)]
+pub struct EnemyGroup {
+    pub encounter: &'static str,
+    /// How likely the group is to be picked, relative to the other groups of the table.
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct EncounterTable {
+    pub groups: Vec<EnemyGroup>,
+    /// How far the player walks between two encounters, picked anew after each one.
+    pub distance: (f32, f32),
+    /// No encounters happen unless all of these hold.
+    pub conditions: Vec<Condition>,
+    /// Counts the battles won in the zone, so that `conditions` can stop the encounters after a
+    /// number of kills.
+    pub kill_counter: Option<&'static str>,
+}
+
+impl EncounterTable {
+    fn pick_distance(&self, rng: &mut Rand32) -> f32 {
+        let (min, max) = self.distance;
+        min + (max - min) * rng.rand_float()
+    }
+
+    fn pick_group(&self, rng: &mut Rand32) -> Option<&EnemyGroup> {
+        let total: u32 = self.groups.iter().map(|group| group.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.rand_range(0..total);
+        self.groups.iter().find(|group| {
+            if roll < group.weight {
+                true
+            } else {
+                roll -= group.weight;
+                false
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreationParams {
+    pub shape: Shape,
+    pub table: EncounterTable,
+}
+
+/// A random encounter started by an encounter zone.
+#[derive(Debug, Copy, Clone)]
+pub struct RandomEncounter {
+    pub encounter: &'static str,
+    pub kill_counter: Option<&'static str>,
+}
+
+/// A zone where the player runs into enemies after walking for a while.
+pub struct EncounterZone {
+    shape: Shape,
+    table: EncounterTable,
+    // How far the player still has to walk in the zone until the next encounter.
+    distance_left: f32,
+}
+
+impl EncounterZone {
+    pub fn new(params: &CreationParams, rng: &mut Rand32) -> Self {
+        Self {
+            shape: params.shape.clone(),
+            distance_left: params.table.pick_distance(rng),
+            table: params.table.clone(),
+        }
+    }
+
+    /// Counts the distance the player has walked this frame, and returns the encounter if it is
+    /// time for one.
+    pub fn update(
+        &mut self,
+        player_hitbox: &OverworldRect<f32>,
+        walked: f32,
+        flags: &Flags,
+        rng: &mut Rand32,
+    ) -> Option<RandomEncounter> {
+        if walked == 0.0
+            || !self.shape.intersects(player_hitbox)
+            || !flags::all_hold(&self.table.conditions, flags)
+        {
+            return None;
+        }
+        self.distance_left -= walked;
+        if self.distance_left > 0.0 {
+            return None;
+        }
+        self.distance_left = self.table.pick_distance(rng);
+        let group = self.table.pick_group(rng)?;
+        Some(RandomEncounter {
+            encounter: group.encounter,
+            kill_counter: self.table.kill_counter,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 49;
+    const ROLLS: usize = 4000;
+
+    fn table(weights: &[(&'static str, u32)]) -> EncounterTable {
+        EncounterTable {
+            groups: weights
+                .iter()
+                .map(|&(encounter, weight)| EnemyGroup { encounter, weight })
+                .collect(),
+            distance: (50.0, 100.0),
+            conditions: Vec::new(),
+            kill_counter: None,
+        }
+    }
+
+    #[test]
+    fn groups_are_picked_by_weight() {
+        let table = table(&[("rare", 1), ("never", 0), ("common", 3)]);
+        let mut rng = Rand32::new(SEED);
+        let mut common = 0;
+        for _ in 0..ROLLS {
+            match table.pick_group(&mut rng).unwrap().encounter {
+                "common" => common += 1,
+                "rare" => (),
+                encounter => panic!("Picked `{}`, which has no weight", encounter),
+            }
+        }
+        let share = common as f32 / ROLLS as f32;
+        assert!((share - 0.75).abs() < 0.03, "common was picked {}", share);
+    }
+
+    #[test]
+    fn nothing_is_picked_without_weight() {
+        let mut rng = Rand32::new(SEED);
+        assert!(table(&[("never", 0), ("nope", 0)])
+            .pick_group(&mut rng)
+            .is_none());
+        assert!(table(&[]).pick_group(&mut rng).is_none());
+    }
+
+    #[test]
+    fn distances_stay_in_range() {
+        let table = table(&[("any", 1)]);
+        let mut rng = Rand32::new(SEED);
+        for _ in 0..ROLLS {
+            let distance = table.pick_distance(&mut rng);
+            assert!((50.0..100.0).contains(&distance), "{}", distance);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_encounters() {
+        let params = CreationParams {
+            shape: Shape::Rect(OverworldRect::new([0.0, 0.0].into(), [10.0, 10.0].into())),
+            table: table(&[("a", 1), ("b", 1), ("c", 1)]),
+        };
+        let player = OverworldRect::new([2.0, 2.0].into(), [4.0, 4.0].into());
+        let encounters = || {
+            let mut rng = Rand32::new(SEED);
+            let mut zone = EncounterZone::new(&params, &mut rng);
+            (0..200)
+                .filter_map(|_| zone.update(&player, 10.0, &Flags::new(), &mut rng))
+                .map(|encounter| encounter.encounter)
+                .collect::<Vec<_>>()
+        };
+        let first = encounters();
+        assert!(first.len() >= 10);
+        assert_eq!(first, encounters());
+    }
+}
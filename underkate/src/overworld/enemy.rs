@@ -0,0 +1,340 @@
+use super::draw_order::YSorted;
+use super::hitbox::{Hitbox, Solid};
+use super::move_trait::{Direction, HasMoveContext, Move, MoveContext, Position};
+use super::movement::{Movement, MovementProfile};
+use super::multiside::{
+    DirectionalTexturePaths, DirectionalTextures, MoveAnimatedMultiside, OneShotAnimation,
+    PlayAnimation,
+};
+use super::pass_map::PassMap;
+use super::walk::{Walk, WalkInfo, WalkState};
+use crate::battle::BattleOutcome;
+use crate::geometry::{OverworldRect, OverworldVector};
+use crate::graphics::texture::Texture;
+use crate::resources::GlobalResourceStorage;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How close an enemy has to get to a point to consider it reached.
+const WAYPOINT_REACH: f32 = 2.0;
+
+/// The distance between the points checked along a line of sight.
+const SIGHT_STEP: f32 = 4.0;
+
+/// How long an enemy keeps looking for the player after losing sight of them.
+const GIVE_UP_AFTER: Duration = Duration::from_secs(3);
+
+/// How long an enemy stays put after the player has fled from the battle with it.
+const FLEE_COOLDOWN: Duration = Duration::from_secs(3);
+
+/// Hitboxes closer than this count as touching. Solid hitboxes never overlap.
+const CONTACT_MARGIN: f32 = 2.0;
+
+#[derive(Debug, Clone)]
+pub struct CreationParams {
+    pub name: String,
+    pub position: Position,
+    pub direction: Direction,
+    pub textures: DirectionalTexturePaths,
+    pub hitbox: OverworldRect<f32>,
+    /// Patrols at the walking speed and chases at the running speed.
+    pub movement: MovementProfile,
+    /// Visited in order, over and over. An enemy without a route guards its initial position.
+    pub patrol: Vec<Position>,
+    pub sight_range: f32,
+    pub encounter: &'static str,
+}
+
+/// What happened to an enemy in its battle. Either way, it is gone from the overworld.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyFate {
+    Defeated,
+    Spared,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum EnemyState {
+    Patrolling {
+        waypoint: usize,
+    },
+    Chasing {
+        last_seen: Position,
+        lost_for: Duration,
+    },
+    /// In a battle with the player.
+    Engaged,
+    Stunned {
+        remaining: Duration,
+    },
+    Resolved(EnemyFate),
+}
+
+pub struct Enemy {
+    name: String,
+    textures: DirectionalTextures,
+    hitbox: OverworldRect<f32>,
+    move_context: MoveContext,
+    walk_state: WalkState,
+    movement: Movement,
+    animation: Option<OneShotAnimation>,
+    patrol: Vec<Position>,
+    sight_range: f32,
+    encounter: &'static str,
+    state: EnemyState,
+}
+
+impl Enemy {
+    pub fn new(params: &CreationParams, resources: &GlobalResourceStorage) -> Self {
+        let patrol = if params.patrol.is_empty() {
+            vec![params.position]
+        } else {
+            params.patrol.clone()
+        };
+        Self {
+            name: params.name.clone(),
+            textures: DirectionalTextures::load(&params.textures, resources),
+            hitbox: params.hitbox,
+            move_context: MoveContext {
+                position: params.position,
+                direction: params.direction,
+            },
+            walk_state: WalkState::default(),
+            movement: Movement::new(params.movement),
+            animation: None,
+            patrol,
+            sight_range: params.sight_range,
+            encounter: params.encounter,
+            state: EnemyState::Patrolling { waypoint: 0 },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn encounter(&self) -> &'static str {
+        self.encounter
+    }
+
+    pub fn fate(&self) -> Option<EnemyFate> {
+        match self.state {
+            EnemyState::Resolved(fate) => Some(fate),
+            _ => None,
+        }
+    }
+
+    /// Whether the enemy is still in the overworld.
+    pub fn is_active(&self) -> bool {
+        self.fate().is_none()
+    }
+
+    pub fn set_fate(&mut self, fate: EnemyFate) {
+        self.state = EnemyState::Resolved(fate);
+        self.stop_walking();
+    }
+
+    /// Whether the enemy would start a battle with the player having this hitbox.
+    pub fn touches(&self, player_hitbox: &OverworldRect<f32>) -> bool {
+        let ready = matches!(
+            self.state,
+            EnemyState::Patrolling { .. } | EnemyState::Chasing { .. }
+        );
+        let reach = self
+            .hitbox_at(self.position())
+            .inflate(CONTACT_MARGIN, CONTACT_MARGIN);
+        ready && reach.intersects(player_hitbox)
+    }
+
+    pub fn engage(&mut self) {
+        self.state = EnemyState::Engaged;
+        self.stop_walking();
+    }
+
+    pub fn resolve(&mut self, outcome: BattleOutcome) {
+        match outcome {
+            BattleOutcome::Won => self.set_fate(EnemyFate::Defeated),
+            BattleOutcome::Spared => self.set_fate(EnemyFate::Spared),
+            BattleOutcome::Fled => {
+                self.state = EnemyState::Stunned {
+                    remaining: FLEE_COOLDOWN,
+                }
+            }
+        }
+    }
+
+    /// Decides where to walk this frame. Patrolling enemies notice the player in front of them,
+    /// and chasing ones follow the player anywhere they can see.
+    pub fn think(
+        &mut self,
+        player_hitbox: &OverworldRect<f32>,
+        pass_map: &impl PassMap,
+        time_slice: Duration,
+    ) {
+        let player_position = player_hitbox.center();
+        let sees_player = self.can_see(player_position, pass_map);
+        self.state = match self.state {
+            EnemyState::Patrolling { .. } if sees_player && self.is_facing(player_position) => {
+                EnemyState::Chasing {
+                    last_seen: player_position,
+                    lost_for: Duration::ZERO,
+                }
+            }
+            EnemyState::Chasing { .. } if sees_player => EnemyState::Chasing {
+                last_seen: player_position,
+                lost_for: Duration::ZERO,
+            },
+            EnemyState::Chasing {
+                last_seen,
+                lost_for,
+            } => {
+                let lost_for = lost_for + time_slice;
+                if lost_for >= GIVE_UP_AFTER || self.has_reached(last_seen) {
+                    self.back_to_patrol()
+                } else {
+                    EnemyState::Chasing {
+                        last_seen,
+                        lost_for,
+                    }
+                }
+            }
+            EnemyState::Stunned { remaining } if remaining <= time_slice => self.back_to_patrol(),
+            EnemyState::Stunned { remaining } => EnemyState::Stunned {
+                remaining: remaining - time_slice,
+            },
+            state => state,
+        };
+
+        match self.state {
+            EnemyState::Patrolling { mut waypoint } => {
+                if self.has_reached(self.patrol[waypoint]) {
+                    waypoint = (waypoint + 1) % self.patrol.len();
+                    self.state = EnemyState::Patrolling { waypoint };
+                }
+                let speed = self.movement.profile().walk_speed;
+                self.walk_towards(self.patrol[waypoint], speed);
+            }
+            EnemyState::Chasing { last_seen, .. } => {
+                let speed = self.movement.profile().run_speed;
+                self.walk_towards(last_seen, speed);
+            }
+            _ => self.stop_walking(),
+        }
+    }
+
+    // Resumes the patrol from the closest waypoint.
+    fn back_to_patrol(&self) -> EnemyState {
+        let distance_to = |point: &Position| (*point - self.position()).length();
+        let waypoint = (0..self.patrol.len())
+            .min_by(|&a, &b| {
+                distance_to(&self.patrol[a])
+                    .partial_cmp(&distance_to(&self.patrol[b]))
+                    .unwrap()
+            })
+            .unwrap_or(0);
+        EnemyState::Patrolling { waypoint }
+    }
+
+    fn has_reached(&self, point: Position) -> bool {
+        (point - self.position()).length() <= WAYPOINT_REACH
+    }
+
+    fn is_facing(&self, point: Position) -> bool {
+        (point - self.position()).dot(self.move_context.direction.to_vector()) > 0.0
+    }
+
+    fn can_see(&self, point: Position, pass_map: &impl PassMap) -> bool {
+        let from = self.hitbox_at(self.position()).center();
+        let delta = point - from;
+        if delta.length() > self.sight_range {
+            return false;
+        }
+        let sample_count = (delta.length() / SIGHT_STEP).ceil().max(1.0) as usize;
+        (0..=sample_count).all(|i| {
+            let sample = from + delta * (i as f32 / sample_count as f32);
+            let probe = OverworldRect::new(sample, sample + OverworldVector::new(1.0, 1.0));
+            !pass_map.collides_with(&probe)
+        })
+    }
+
+    fn walk_towards(&mut self, point: Position, speed: f32) {
+        if self.has_reached(point) {
+            self.stop_walking();
+            return;
+        }
+        let heading = (point - self.position()).normalize();
+        self.start_walking(WalkInfo {
+            velocity: heading * speed,
+        });
+        self.set_direction(Direction::from_vector(heading));
+    }
+}
+
+impl AsRef<MoveContext> for Enemy {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Enemy {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Enemy {}
+
+impl PlayAnimation for Enemy {
+    fn one_shot_animation(&self) -> Option<&OneShotAnimation> {
+        self.animation.as_ref()
+    }
+
+    fn set_one_shot_animation(&mut self, animation: Option<OneShotAnimation>) {
+        self.animation = animation
+    }
+}
+
+impl MoveAnimatedMultiside for Enemy {
+    fn texture_for_direction(&self, direction: Direction) -> &Texture {
+        self.textures.for_direction(direction)
+    }
+
+    fn is_moving(&self) -> bool {
+        matches!(self.walk_state, WalkState::Walking(_))
+    }
+
+    fn direction(&self) -> Direction {
+        self.move_context.direction
+    }
+}
+
+impl Walk for Enemy {
+    fn walk_state(&self) -> WalkState {
+        self.walk_state
+    }
+
+    fn set_walk_state(&mut self, walk_state: WalkState) {
+        self.walk_state = walk_state
+    }
+
+    fn movement(&self) -> &Movement {
+        &self.movement
+    }
+
+    fn movement_mut(&mut self) -> &mut Movement {
+        &mut self.movement
+    }
+}
+
+impl Hitbox for Enemy {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.hitbox
+    }
+}
+
+impl Solid for Enemy {
+    fn is_solid(&self) -> bool {
+        self.is_active()
+    }
+}
+
+impl YSorted for Enemy {}
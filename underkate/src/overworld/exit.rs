@@ -0,0 +1,7 @@
+use crate::geometry::OverworldRect;
+
+#[derive(Debug, Clone)]
+pub struct Exit {
+    pub rect: OverworldRect<f32>,
+    pub target_room: String,
+}
@@ -0,0 +1,186 @@
+use super::draw_order::YSorted;
+use super::hitbox::Hitbox;
+use super::move_trait::{Direction, HasMoveContext, MoveContext, Position};
+use super::multiside::{
+    DirectionalTexturePaths, DirectionalTextures, MoveAnimatedMultiside, OneShotAnimation,
+    PlayAnimation,
+};
+use crate::game_context::GameContext;
+use crate::geometry::OverworldRect;
+use crate::graphics::texture::Texture;
+use crate::resources::GlobalResourceStorage;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct CreationParams {
+    pub name: String,
+    pub textures: DirectionalTexturePaths,
+    pub hitbox: OverworldRect<f32>,
+    /// How far behind the player the follower walks, measured along the player's path.
+    pub distance: f32,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Breadcrumb {
+    position: Position,
+    direction: Direction,
+}
+
+/// A party member who walks behind the player, retracing their steps.
+pub struct Follower {
+    // Kept so that the party can be saved.
+    params: CreationParams,
+    textures: DirectionalTextures,
+    move_context: MoveContext,
+    // The positions the player has passed, the most recent first. Only the ones needed to place
+    // the follower are kept.
+    trail: VecDeque<Breadcrumb>,
+    moving: bool,
+    animation: Option<OneShotAnimation>,
+}
+
+impl Follower {
+    // The follower is placed on the player when added to a room.
+    pub fn new(params: &CreationParams, resources: &GlobalResourceStorage) -> Self {
+        Self {
+            params: params.clone(),
+            textures: DirectionalTextures::load(&params.textures, resources),
+            move_context: MoveContext {
+                position: Position::origin(),
+                direction: Direction::Backward,
+            },
+            trail: VecDeque::new(),
+            moving: false,
+            animation: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+
+    pub fn params(&self) -> &CreationParams {
+        &self.params
+    }
+
+    /// Forgets the trail and puts the follower right on the leader.
+    pub fn reset(&mut self, leader: &MoveContext) {
+        let breadcrumb = Breadcrumb {
+            position: leader.position,
+            direction: leader.direction,
+        };
+        self.trail.clear();
+        self.trail.push_front(breadcrumb);
+        self.move_context.position = breadcrumb.position;
+        self.move_context.direction = breadcrumb.direction;
+        self.moving = false;
+    }
+
+    /// Records the leader's current state and moves the follower along the trail.
+    pub fn follow(&mut self, leader: &MoveContext) {
+        if self
+            .trail
+            .front()
+            .is_none_or(|last| last.position != leader.position)
+        {
+            self.trail.push_front(Breadcrumb {
+                position: leader.position,
+                direction: leader.direction,
+            });
+        }
+        let breadcrumb = self.breadcrumb_behind();
+        self.moving = breadcrumb.position != self.move_context.position;
+        self.move_context.position = breadcrumb.position;
+        self.move_context.direction = breadcrumb.direction;
+    }
+
+    // Walks back along the trail by `distance`, dropping the breadcrumbs past that point. If the
+    // trail is shorter than that, the follower waits at its start.
+    fn breadcrumb_behind(&mut self) -> Breadcrumb {
+        let mut remaining = self.params.distance;
+        for index in 1..self.trail.len() {
+            let newer = self.trail[index - 1];
+            let older = self.trail[index];
+            let segment = (older.position - newer.position).length();
+            if segment >= remaining {
+                self.trail.truncate(index + 1);
+                return Breadcrumb {
+                    position: newer.position.lerp(older.position, remaining / segment),
+                    direction: newer.direction,
+                };
+            }
+            remaining -= segment;
+        }
+        *self.trail.back().expect("The trail of a follower is empty")
+    }
+}
+
+impl AsRef<MoveContext> for Follower {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Follower {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Follower {}
+
+impl PlayAnimation for Follower {
+    fn one_shot_animation(&self) -> Option<&OneShotAnimation> {
+        self.animation.as_ref()
+    }
+
+    fn set_one_shot_animation(&mut self, animation: Option<OneShotAnimation>) {
+        self.animation = animation
+    }
+}
+
+impl MoveAnimatedMultiside for Follower {
+    fn texture_for_direction(&self, direction: Direction) -> &Texture {
+        self.textures.for_direction(direction)
+    }
+
+    fn is_moving(&self) -> bool {
+        self.moving
+    }
+
+    fn direction(&self) -> Direction {
+        self.move_context.direction
+    }
+}
+
+impl Hitbox for Follower {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.params.hitbox
+    }
+}
+
+impl YSorted for Follower {}
+
+/// Adds a follower behind the player. It stays in the party across rooms until removed.
+pub fn add_follower(context: &GameContext, params: &CreationParams) {
+    let follower = Follower::new(params, &context.global_resource_storage);
+    context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .room_mut()
+        .add_follower(follower);
+}
+
+/// Returns `false` if there is no follower with this name.
+pub fn remove_follower(context: &GameContext, name: &str) -> bool {
+    context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .room_mut()
+        .remove_follower(name)
+        .is_some()
+}
@@ -1,10 +1,27 @@
-use crate::geometry::{OverworldPoint, ScreenPoint};
+use crate::geometry::{OverworldPoint, OverworldVector, ScreenPoint};
 
 #[derive(Debug, Copy, Clone)]
-pub struct TranslationContext;
+pub struct TranslationContext {
+    view_origin: OverworldVector<f32>,
+}
 
 impl TranslationContext {
-    pub fn to_screen<T: Copy>(&self, point: OverworldPoint<T>) -> ScreenPoint<T> {
-        point.cast_unit()
+    /// `view_origin` is the overworld point shown at the top-left corner of the screen.
+    pub fn new(view_origin: OverworldPoint<f32>) -> Self {
+        Self {
+            view_origin: view_origin.to_vector(),
+        }
+    }
+
+    /// A context for things that scroll `factor` times as fast as the world: 0 keeps them fixed
+    /// on the screen, values below 1 make them look farther away than the world itself.
+    pub fn with_parallax(self, factor: f32) -> Self {
+        Self {
+            view_origin: self.view_origin * factor,
+        }
+    }
+
+    pub fn to_screen(self, point: OverworldPoint<f32>) -> ScreenPoint<f32> {
+        (point - self.view_origin).cast_unit()
     }
 }
@@ -1,4 +1,4 @@
-use super::move_trait::Position;
+use super::move_trait::{Move, Position};
 use crate::geometry::OverworldRect;
 
 pub trait Hitbox {
@@ -8,3 +8,15 @@ pub trait Hitbox {
         self.hitbox_at_origin().translate(point.to_vector())
     }
 }
+
+pub trait Solid: Hitbox + Move {
+    fn is_solid(&self) -> bool;
+
+    fn solid_hitbox(&self) -> Option<OverworldRect<f32>> {
+        if self.is_solid() {
+            Some(self.hitbox_at(self.position()))
+        } else {
+            None
+        }
+    }
+}
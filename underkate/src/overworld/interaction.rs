@@ -0,0 +1,59 @@
+use super::hitbox::Hitbox;
+use super::move_trait::{Direction, Move};
+use crate::geometry::OverworldRect;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use crate::script::rust_script::RustScript;
+use crate::script::Script;
+
+const INTERACTION_REACH: f32 = 24.0;
+
+pub trait Interact: Hitbox + Move {
+    fn interaction_script_mut(&mut self) -> Option<&mut Box<dyn Script>>;
+    fn can_interact(&self) -> bool;
+
+    fn interaction_rect(&self) -> OverworldRect<f32> {
+        self.hitbox_at(self.position())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ZoneCreationParams {
+    pub rect: OverworldRect<f32>,
+    pub script: &'static str,
+}
+
+/// A part of the room, such as a bookshelf drawn on the background, which can be examined.
+pub struct InteractionZone {
+    rect: OverworldRect<f32>,
+    script: Box<dyn Script>,
+}
+
+impl InteractionZone {
+    pub fn new(params: &ZoneCreationParams, resources: &GlobalResourceStorage) -> Self {
+        let script: RustScript = resources.get_cloned(params.script);
+        Self {
+            rect: params.rect,
+            script: Box::new(script),
+        }
+    }
+
+    pub fn rect(&self) -> OverworldRect<f32> {
+        self.rect
+    }
+
+    pub fn script_mut(&mut self) -> &mut Box<dyn Script> {
+        &mut self.script
+    }
+}
+
+/// The area right in front of a hitbox facing `direction`.
+pub fn probe_rect(hitbox: &OverworldRect<f32>, direction: Direction) -> OverworldRect<f32> {
+    let OverworldRect { min, max, .. } = *hitbox;
+    let (probe_min, probe_max) = match direction {
+        Direction::Forward => ([min.x, min.y - INTERACTION_REACH], [max.x, min.y]),
+        Direction::Backward => ([min.x, max.y], [max.x, max.y + INTERACTION_REACH]),
+        Direction::Left => ([min.x - INTERACTION_REACH, min.y], [min.x, max.y]),
+        Direction::Right => ([max.x, min.y], [max.x + INTERACTION_REACH, max.y]),
+    };
+    OverworldRect::new(probe_min.into(), probe_max.into())
+}
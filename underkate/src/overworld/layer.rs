@@ -0,0 +1,53 @@
+use super::geometry::TranslationContext;
+use crate::geometry::OverworldPoint;
+use crate::graphics::texture::Texture;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use ggez::{Context, GameResult};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ZOrder {
+    BelowEntities,
+    AboveEntities,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreationParams {
+    pub texture_path: String,
+    pub z_order: ZOrder,
+    pub parallax: f32,
+    pub opacity: f32,
+}
+
+/// A room-sized picture, such as the floor or tree canopies, whose top-left corner is at the
+/// room's origin. Animated textures keep animating.
+pub struct Layer {
+    texture: Texture,
+    z_order: ZOrder,
+    parallax: f32,
+    opacity: f32,
+}
+
+impl Layer {
+    pub fn new(params: &CreationParams, resources: &GlobalResourceStorage) -> Self {
+        Self {
+            texture: resources.get_cloned(&params.texture_path),
+            z_order: params.z_order,
+            parallax: params.parallax,
+            opacity: params.opacity,
+        }
+    }
+
+    pub fn z_order(&self) -> ZOrder {
+        self.z_order
+    }
+
+    pub fn draw(&self, ctx: &mut Context, translation_context: &TranslationContext) -> GameResult {
+        let center: OverworldPoint<f32> =
+            (self.texture.dimensions().to_vector().to_point() * 0.5).cast_unit();
+        let screen_center = translation_context
+            .with_parallax(self.parallax)
+            .to_screen(center);
+        self.texture
+            .draw_with_opacity(ctx, screen_center, self.opacity)
+    }
+}
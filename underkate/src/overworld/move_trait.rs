@@ -1,8 +1,9 @@
-use crate::geometry::OverworldPoint;
+use crate::geometry::{OverworldPoint, OverworldVector};
+use serde::{Deserialize, Serialize};
 
 pub type Position = OverworldPoint<f32>;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     Left,
     Right,
@@ -10,6 +11,33 @@ pub enum Direction {
     Backward,
 }
 
+impl Direction {
+    /// The direction closest to the vector. Horizontal directions win ties.
+    pub fn from_vector(vector: OverworldVector<f32>) -> Self {
+        if vector.x.abs() >= vector.y.abs() {
+            if vector.x > 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if vector.y > 0.0 {
+            Direction::Backward
+        } else {
+            Direction::Forward
+        }
+    }
+
+    /// The unit vector pointing in the direction.
+    pub fn to_vector(self) -> OverworldVector<f32> {
+        match self {
+            Direction::Left => OverworldVector::new(-1.0, 0.0),
+            Direction::Right => OverworldVector::new(1.0, 0.0),
+            Direction::Forward => OverworldVector::new(0.0, -1.0),
+            Direction::Backward => OverworldVector::new(0.0, 1.0),
+        }
+    }
+}
+
 pub trait Move {
     fn position(&self) -> Position;
     fn set_position(&mut self, position: Position);
@@ -0,0 +1,153 @@
+use crate::geometry::OverworldVector;
+use std::time::Duration;
+
+/// Moves that end closer than this to where they were headed count as unobstructed.
+const MIN_SHORTFALL: f32 = 1e-3;
+
+/// How an entity walks. Speeds are in overworld units per second, acceleration and deceleration
+/// in units per second squared.
+#[derive(Debug, Copy, Clone)]
+pub struct MovementProfile {
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    /// `None` means that the entity reaches its speed instantly.
+    pub acceleration: Option<f32>,
+    /// `None` means that the entity stops instantly.
+    pub deceleration: Option<f32>,
+}
+
+impl MovementProfile {
+    pub fn speed(&self, running: bool) -> f32 {
+        if running {
+            self.run_speed
+        } else {
+            self.walk_speed
+        }
+    }
+}
+
+impl Default for MovementProfile {
+    fn default() -> Self {
+        Self {
+            walk_speed: 120.0,
+            run_speed: 200.0,
+            acceleration: None,
+            deceleration: None,
+        }
+    }
+}
+
+/// The actual velocity of an entity, which follows the velocity it wants to walk with.
+#[derive(Debug, Clone)]
+pub struct Movement {
+    profile: MovementProfile,
+    speed_multiplier: f32,
+    terrain_factor: f32,
+    velocity: OverworldVector<f32>,
+}
+
+impl Movement {
+    pub fn new(profile: MovementProfile) -> Self {
+        Self {
+            profile,
+            speed_multiplier: 1.0,
+            terrain_factor: 1.0,
+            velocity: OverworldVector::zero(),
+        }
+    }
+
+    pub fn profile(&self) -> &MovementProfile {
+        &self.profile
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        self.speed_multiplier
+    }
+
+    /// Scales every velocity of the entity, e.g. to slow it down during a cutscene.
+    pub fn set_speed_multiplier(&mut self, speed_multiplier: f32) {
+        self.speed_multiplier = speed_multiplier;
+    }
+
+    /// Set by the room from the terrain under the entity.
+    pub fn set_terrain_factor(&mut self, terrain_factor: f32) {
+        self.terrain_factor = terrain_factor;
+    }
+
+    /// Brings the velocity closer to `target`, which gets scaled by the multipliers first, and
+    /// returns the new velocity.
+    pub fn update_velocity(
+        &mut self,
+        target: OverworldVector<f32>,
+        time_slice: Duration,
+    ) -> OverworldVector<f32> {
+        let target = target * self.speed_multiplier * self.terrain_factor;
+        let rate = if target.square_length() >= self.velocity.square_length() {
+            self.profile.acceleration
+        } else {
+            self.profile.deceleration
+        };
+        let change = target - self.velocity;
+        self.velocity = match rate {
+            Some(rate) if change.length() > rate * time_slice.as_secs_f32() => {
+                self.velocity + change.normalize() * rate * time_slice.as_secs_f32()
+            }
+            _ => target,
+        };
+        self.velocity
+    }
+
+    /// Cancels the part of the velocity that keeps pushing into an obstacle, given how far short
+    /// of the intended position the last move ended. Otherwise an accelerating entity would
+    /// build up speed against a wall and shoot off as soon as it got past.
+    pub fn stop_against(&mut self, shortfall: OverworldVector<f32>) {
+        if shortfall.length() < MIN_SHORTFALL {
+            return;
+        }
+        let blocked = shortfall.normalize();
+        let into_obstacle = self.velocity.dot(blocked);
+        if into_obstacle > 0.0 {
+            self.velocity -= blocked * into_obstacle;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn accelerating() -> Movement {
+        Movement::new(MovementProfile {
+            acceleration: Some(100.0),
+            deceleration: Some(100.0),
+            ..MovementProfile::default()
+        })
+    }
+
+    #[test]
+    fn wall_cancels_only_the_blocked_component() {
+        let mut movement = accelerating();
+        let velocity = movement.update_velocity(OverworldVector::new(100.0, 100.0), FRAME);
+        movement.stop_against(OverworldVector::new(0.0, 0.5));
+        assert_eq!(movement.velocity, OverworldVector::new(velocity.x, 0.0));
+    }
+
+    #[test]
+    fn diagonal_wall_keeps_the_part_along_it() {
+        let mut movement = accelerating();
+        movement.update_velocity(OverworldVector::new(100.0, 0.0), FRAME);
+        movement.stop_against(OverworldVector::new(0.5, 0.5));
+        let velocity = movement.velocity;
+        assert!((velocity.x - 5.0).abs() < 1e-4 && (velocity.y + 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn unobstructed_moves_keep_the_velocity() {
+        let mut movement = accelerating();
+        let velocity = movement.update_velocity(OverworldVector::new(100.0, 0.0), FRAME);
+        movement.stop_against(OverworldVector::new(1e-5, 0.0));
+        assert_eq!(movement.velocity, velocity);
+    }
+}
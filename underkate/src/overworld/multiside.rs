@@ -2,9 +2,41 @@ use super::move_trait::Direction;
 use crate::geometry::ScreenPoint;
 use crate::graphics::texture::Texture;
 use crate::graphics::Draw;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
 use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
 
-pub trait MoveAnimatedMultiside {
+/// An animation which replaces the usual textures of an entity until it has been shown once.
+#[derive(Debug, Clone)]
+pub struct OneShotAnimation {
+    texture: Texture,
+    ends_at: Instant,
+}
+
+impl OneShotAnimation {
+    pub fn start(texture: &Texture) -> Self {
+        Self {
+            texture: texture.restarted(),
+            ends_at: Instant::now() + texture.animation_duration(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        Instant::now() >= self.ends_at
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+}
+
+pub trait PlayAnimation {
+    fn one_shot_animation(&self) -> Option<&OneShotAnimation>;
+    fn set_one_shot_animation(&mut self, animation: Option<OneShotAnimation>);
+}
+
+pub trait MoveAnimatedMultiside: PlayAnimation {
     fn texture_for_direction(&self, direction: Direction) -> &Texture;
     fn is_moving(&self) -> bool;
     fn direction(&self) -> Direction;
@@ -12,6 +44,11 @@ pub trait MoveAnimatedMultiside {
 
 impl<T: MoveAnimatedMultiside> Draw for T {
     fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult {
+        if let Some(animation) = self.one_shot_animation() {
+            if !animation.is_finished() {
+                return animation.texture.draw(ctx, center_at);
+            }
+        }
         let texture = self.texture_for_direction(self.direction());
         if self.is_moving() {
             texture.draw(ctx, center_at)
@@ -20,3 +57,39 @@ impl<T: MoveAnimatedMultiside> Draw for T {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectionalTexturePaths {
+    pub left: String,
+    pub right: String,
+    pub forward: String,
+    pub backward: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DirectionalTextures {
+    left: Texture,
+    right: Texture,
+    forward: Texture,
+    backward: Texture,
+}
+
+impl DirectionalTextures {
+    pub fn load(paths: &DirectionalTexturePaths, resources: &GlobalResourceStorage) -> Self {
+        Self {
+            left: resources.get_cloned(&paths.left),
+            right: resources.get_cloned(&paths.right),
+            forward: resources.get_cloned(&paths.forward),
+            backward: resources.get_cloned(&paths.backward),
+        }
+    }
+
+    pub fn for_direction(&self, direction: Direction) -> &Texture {
+        match direction {
+            Direction::Left => &self.left,
+            Direction::Right => &self.right,
+            Direction::Forward => &self.forward,
+            Direction::Backward => &self.backward,
+        }
+    }
+}
@@ -2,28 +2,25 @@ use super::walk::{Walk, WalkInfo};
 use crate::geometry::OverworldVector;
 use paste::paste;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub struct MultiWalkState {
-    velocity_abs: f32,
     walking_forward: bool,
     walking_backward: bool,
     walking_left: bool,
     walking_right: bool,
+    running: bool,
 }
 
 impl MultiWalkState {
-    pub fn new(velocity_abs: f32) -> Self {
-        Self {
-            velocity_abs,
-            walking_forward: false,
-            walking_backward: false,
-            walking_left: false,
-            walking_right: false,
-        }
+    pub fn new() -> Self {
+        Self::default()
     }
-    pub fn resulting_velocity(&self) -> OverworldVector<f32> {
+
+    /// The unit vector pointing where the entity walks, so that walking diagonally is not any
+    /// faster than walking straight. Zero if the entity stands still.
+    pub fn direction(&self) -> OverworldVector<f32> {
         // TODO: use std::array::IntoIter when it is stabilized.
-        [
+        let sum: OverworldVector<f32> = [
             (self.walking_forward, 0.0, -1.0),
             (self.walking_backward, 0.0, 1.0),
             (self.walking_left, -1.0, 0.0),
@@ -31,12 +28,10 @@ impl MultiWalkState {
         ]
         .iter()
         .copied()
-        .map(|(enabled, x_coeff, y_coeff)| {
-            OverworldVector::new(x_coeff, y_coeff)
-                * self.velocity_abs
-                * if enabled { 1.0 } else { 0.0 }
-        })
-        .sum()
+        .filter(|&(enabled, _, _)| enabled)
+        .map(|(_, x_coeff, y_coeff)| OverworldVector::new(x_coeff, y_coeff))
+        .sum();
+        sum.try_normalize().unwrap_or_else(OverworldVector::zero)
     }
 
     pub fn is_still(&self) -> bool {
@@ -76,12 +71,30 @@ pub trait MultiWalk: Walk {
         if state.is_still() {
             self.stop_walking();
         } else {
+            let speed = self.movement().profile().speed(state.running);
             self.start_walking(WalkInfo {
-                velocity: state.resulting_velocity(),
+                velocity: state.direction() * speed,
             })
         }
     }
 
+    fn set_running(&mut self, running: bool) {
+        let mut state = self.multi_walk_state();
+        state.running = running;
+        self.set_multi_walk_state(state);
+        self.update_walk_state();
+    }
+
+    fn stop_walking_in_all_directions(&mut self) {
+        let mut state = self.multi_walk_state();
+        state.walking_forward = false;
+        state.walking_backward = false;
+        state.walking_left = false;
+        state.walking_right = false;
+        self.set_multi_walk_state(state);
+        self.update_walk_state();
+    }
+
     gen_methods_for_direction!(forward);
     gen_methods_for_direction!(backward);
     gen_methods_for_direction!(left);
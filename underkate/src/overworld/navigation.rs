@@ -0,0 +1,598 @@
+use super::choreography::Cutscene;
+use super::hitbox::Hitbox;
+use super::move_trait::{Direction, Position};
+use super::multiside::PlayAnimation;
+use super::pass_map::PassMap;
+use super::passability_checker::{PassMapPassabilityChecker, PassabilityCheck};
+use super::room::{EntityId, Room};
+use super::walk::{Walk, WalkInfo};
+use crate::game_context::GameContext;
+use crate::geometry::{OverworldRect, OverworldVector};
+use crate::script::wait_until;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// The side of a navigation grid cell. Gaps narrower than the agent plus a cell may be missed.
+pub const CELL_SIZE: f32 = 8.0;
+
+/// How close to a waypoint an entity has to get for it to count as reached.
+const ARRIVAL_DISTANCE: f32 = 2.0;
+
+/// An entity that hasn't moved for this many frames is considered stuck.
+const STUCK_FRAMES: usize = 30;
+
+// Step costs, in tenths of a cell.
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Anything that can be walked along a path.
+pub trait Agent: Walk + Hitbox + PlayAnimation {}
+
+impl<T: Walk + Hitbox + PlayAnimation> Agent for T {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NavigationError {
+    NoSuchEntity(EntityId),
+    /// The agent doesn't fit at the target.
+    TargetBlocked,
+    NoPath,
+    /// Something the grid doesn't know about, such as another entity, is in the way.
+    Stuck,
+}
+
+impl Display for NavigationError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::NoSuchEntity(entity) => write!(fmt, "Entity {:?} does not exist", entity),
+            Self::TargetBlocked => write!(fmt, "The target position is not passable"),
+            Self::NoPath => write!(fmt, "There is no path to the target position"),
+            Self::Stuck => write!(fmt, "The entity got stuck on its way"),
+        }
+    }
+}
+
+impl Error for NavigationError {}
+
+/// Passability of a pass map sampled on a grid, for an agent with the given hitbox. A cell is
+/// free if the hitbox fits when the agent stands in the middle of the cell.
+pub struct NavGrid<'a, P: PassMap> {
+    pass: PassMapPassabilityChecker<'a, P>,
+    hitbox: OverworldRect<f32>,
+    origin: Position,
+    width: usize,
+    height: usize,
+    free: Vec<bool>,
+}
+
+impl<'a, P: PassMap> NavGrid<'a, P> {
+    pub fn new(pass_map: &'a P, hitbox: OverworldRect<f32>) -> Self {
+        let pass = PassMapPassabilityChecker::new(pass_map);
+        let bounds = pass_map.bounds();
+        let width = (bounds.width() / CELL_SIZE).floor() as usize;
+        let height = (bounds.height() / CELL_SIZE).floor() as usize;
+        let mut grid = Self {
+            pass,
+            hitbox,
+            origin: bounds.min,
+            width,
+            height,
+            free: Vec::new(),
+        };
+        grid.free = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|cell| grid.fits_at(grid.cell_center(cell)))
+            .collect();
+        grid
+    }
+
+    fn fits_at(&self, position: Position) -> bool {
+        self.pass
+            .can_pass(&self.hitbox.translate(position.to_vector()))
+    }
+
+    fn cell_center(&self, (x, y): (usize, usize)) -> Position {
+        self.origin + OverworldVector::new(x as f32 + 0.5, y as f32 + 0.5) * CELL_SIZE
+    }
+
+    fn is_free(&self, x: isize, y: isize) -> bool {
+        let in_bounds = x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height;
+        in_bounds && self.free[y as usize * self.width + x as usize]
+    }
+
+    // The free cell closest to the position from which the position itself can be reached.
+    fn nearest_free_cell(&self, position: Position) -> Option<(usize, usize)> {
+        let relative = (position - self.origin) / CELL_SIZE;
+        let (cell_x, cell_y) = (relative.x.floor() as isize, relative.y.floor() as isize);
+        let distance_to = |(x, y): (usize, usize)| (self.cell_center((x, y)) - position).length();
+        (cell_y - 1..=cell_y + 1)
+            .flat_map(|y| (cell_x - 1..=cell_x + 1).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_free(x, y))
+            .map(|(x, y)| (x as usize, y as usize))
+            .filter(|&cell| self.is_line_clear(self.cell_center(cell), position))
+            .min_by(|&a, &b| distance_to(a).partial_cmp(&distance_to(b)).unwrap())
+    }
+
+    /// Whether the agent can walk straight from `from` to `to`.
+    pub fn is_line_clear(&self, from: Position, to: Position) -> bool {
+        let delta = to - from;
+        let sample_count = (delta.length() / (CELL_SIZE / 4.0)).ceil().max(1.0) as usize;
+        (0..=sample_count).all(|i| self.fits_at(from + delta * (i as f32 / sample_count as f32)))
+    }
+
+    fn octile_distance(a: (usize, usize), b: (usize, usize)) -> u32 {
+        let dx = (a.0 as i64 - b.0 as i64).unsigned_abs() as u32;
+        let dy = (a.1 as i64 - b.1 as i64).unsigned_abs() as u32;
+        STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+    }
+
+    fn find_cell_path(&self, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<Position>> {
+        let index = |(x, y): (usize, usize)| y * self.width + x;
+        let mut cost = vec![u32::MAX; self.free.len()];
+        let mut came_from = vec![usize::MAX; self.free.len()];
+        let mut open = BinaryHeap::new();
+        cost[index(start)] = 0;
+        open.push(Reverse((Self::octile_distance(start, goal), index(start))));
+
+        while let Some(Reverse((_, current))) = open.pop() {
+            let cell = (current % self.width, current / self.width);
+            if cell == goal {
+                let mut path = VecDeque::new();
+                let mut node = current;
+                while node != index(start) {
+                    path.push_front(self.cell_center((node % self.width, node / self.width)));
+                    node = came_from[node];
+                }
+                path.push_front(self.cell_center(start));
+                return Some(path.into());
+            }
+
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    let (x, y) = (cell.0 as isize + dx, cell.1 as isize + dy);
+                    // Diagonal steps must not cut corners.
+                    let free = self.is_free(x, y)
+                        && self.is_free(x, cell.1 as isize)
+                        && self.is_free(cell.0 as isize, y);
+                    if !free {
+                        continue;
+                    }
+                    let neighbour = (x as usize, y as usize);
+                    let step_cost = if dx != 0 && dy != 0 {
+                        DIAGONAL_COST
+                    } else {
+                        STRAIGHT_COST
+                    };
+                    let new_cost = cost[current] + step_cost;
+                    if new_cost < cost[index(neighbour)] {
+                        cost[index(neighbour)] = new_cost;
+                        came_from[index(neighbour)] = current;
+                        let estimate = new_cost + Self::octile_distance(neighbour, goal);
+                        open.push(Reverse((estimate, index(neighbour))));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Drops every waypoint that can be skipped by walking in a straight line.
+    fn smooth(&self, points: Vec<Position>) -> Vec<Position> {
+        let mut smoothed = Vec::new();
+        let mut anchor = 0;
+        while anchor + 1 < points.len() {
+            let next = (anchor + 1..points.len())
+                .rev()
+                .find(|&candidate| self.is_line_clear(points[anchor], points[candidate]))
+                .unwrap_or(anchor + 1);
+            smoothed.push(points[next]);
+            anchor = next;
+        }
+        smoothed
+    }
+
+    /// Returns the waypoints leading from `from` to `to`, not including `from`.
+    pub fn find_path(
+        &self,
+        from: Position,
+        to: Position,
+    ) -> Result<Vec<Position>, NavigationError> {
+        if !self.fits_at(to) {
+            return Err(NavigationError::TargetBlocked);
+        }
+        if self.is_line_clear(from, to) {
+            return Ok(vec![to]);
+        }
+        let start = self
+            .nearest_free_cell(from)
+            .ok_or(NavigationError::NoPath)?;
+        let goal = self.nearest_free_cell(to).ok_or(NavigationError::NoPath)?;
+        let cells = self
+            .find_cell_path(start, goal)
+            .ok_or(NavigationError::NoPath)?;
+        let points = std::iter::once(from)
+            .chain(cells)
+            .chain(std::iter::once(to))
+            .collect();
+        Ok(self.smooth(points))
+    }
+}
+
+/// Plans a path for the entity in the current room.
+pub fn find_path(
+    room: &mut Room,
+    entity: &EntityId,
+    to: Position,
+) -> Result<Vec<Position>, NavigationError> {
+    let agent = room
+        .entity_mut(entity)
+        .ok_or_else(|| NavigationError::NoSuchEntity(entity.clone()))?;
+    let (hitbox, from) = (agent.hitbox_at_origin(), agent.position());
+    NavGrid::new(room.pass_map(), hitbox).find_path(from, to)
+}
+
+struct PathWalk {
+    entity: EntityId,
+    waypoints: VecDeque<Position>,
+    speed: Option<f32>,
+    // The direction of the current leg of the path, zero until the walk starts.
+    heading: OverworldVector<f32>,
+    last_position: Option<Position>,
+    still_frames: usize,
+}
+
+impl PathWalk {
+    fn new(entity: EntityId, waypoints: Vec<Position>, speed: Option<f32>) -> Self {
+        Self {
+            entity,
+            waypoints: waypoints.into(),
+            speed,
+            heading: OverworldVector::zero(),
+            last_position: None,
+            still_frames: 0,
+        }
+    }
+
+    // Called once per frame; returns the outcome when the walk is over.
+    fn step(&mut self, room: &mut Room) -> Option<Result<(), NavigationError>> {
+        let agent = match room.entity_mut(&self.entity) {
+            Some(agent) => agent,
+            None => return Some(Err(NavigationError::NoSuchEntity(self.entity.clone()))),
+        };
+        let target = *self.waypoints.back()?;
+        let outcome = self.steer(agent)?;
+        if outcome.is_ok() && agent.position() != target {
+            self.snap_to(room, target);
+        }
+        Some(outcome)
+    }
+
+    // Points the agent at the next waypoint, dropping the ones it has reached.
+    fn steer(&mut self, agent: &mut dyn Agent) -> Option<Result<(), NavigationError>> {
+        let position = agent.position();
+
+        while let Some(&waypoint) = self.waypoints.front() {
+            let to_waypoint = waypoint - position;
+            // A waypoint left behind has been overshot within the last frame.
+            let passed =
+                self.heading != OverworldVector::zero() && to_waypoint.dot(self.heading) <= 0.0;
+            if to_waypoint.length() > ARRIVAL_DISTANCE && !passed {
+                break;
+            }
+            self.waypoints.pop_front();
+            match self.waypoints.front() {
+                Some(&next) if next != waypoint => self.heading = (next - waypoint).normalize(),
+                Some(_) => (),
+                None => {
+                    agent.stop_walking();
+                    return Some(Ok(()));
+                }
+            }
+        }
+
+        if self.last_position == Some(position) {
+            self.still_frames += 1;
+            if self.still_frames >= STUCK_FRAMES {
+                agent.stop_walking();
+                return Some(Err(NavigationError::Stuck));
+            }
+        } else {
+            self.still_frames = 0;
+        }
+        self.last_position = Some(position);
+
+        let waypoint = self.waypoints[0];
+        self.heading = (waypoint - position).normalize();
+        let speed = self
+            .speed
+            .unwrap_or_else(|| agent.movement().profile().walk_speed);
+        agent.start_walking(WalkInfo {
+            velocity: self.heading * speed,
+        });
+        agent.set_direction(Direction::from_vector(self.heading));
+        None
+    }
+
+    // Moves the entity exactly onto its target, unless that would put it into a wall.
+    fn snap_to(&self, room: &mut Room, target: Position) {
+        let hitbox = match room.entity_mut(&self.entity) {
+            Some(agent) => agent.hitbox_at(target),
+            None => return,
+        };
+        if PassMapPassabilityChecker::new(room.pass_map()).can_pass(&hitbox) {
+            if let Some(agent) = room.entity_mut(&self.entity) {
+                agent.set_position(target);
+            }
+        }
+    }
+}
+
+/// Walks the entity to `target` around the obstacles of the current room, resolving once it
+/// arrives or fails to.
+pub async fn walk_to(
+    context: &GameContext,
+    entity: EntityId,
+    target: Position,
+) -> Result<(), NavigationError> {
+    let waypoints = {
+        let screen = context.overworld_screen.lock().unwrap();
+        let mut screen = screen.borrow_mut();
+        find_path(screen.room_mut(), &entity, target)?
+    };
+    follow_path(context, entity, waypoints, None).await
+}
+
+/// Walks the entity through the waypoints in straight lines, with the walking speed of its
+/// movement profile unless `speed` is given. The player's controls are taken away meanwhile.
+pub async fn follow_path(
+    context: &GameContext,
+    entity: EntityId,
+    waypoints: Vec<Position>,
+    speed: Option<f32>,
+) -> Result<(), NavigationError> {
+    if waypoints.is_empty() {
+        return Ok(());
+    }
+    let _cutscene = (entity == EntityId::Player).then(|| Cutscene::begin(context));
+    let mut walk = PathWalk::new(entity, waypoints, speed);
+    let mut outcome = None;
+    wait_until(|| {
+        let screen = context.overworld_screen.lock().unwrap();
+        let mut screen = screen.borrow_mut();
+        // Nobody moves while the room is paused, which must not look like being stuck.
+        if screen.is_room_paused() {
+            return false;
+        }
+        outcome = walk.step(screen.room_mut());
+        outcome.is_some()
+    })
+    .await;
+    outcome.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overworld::move_trait::{HasMoveContext, Move, MoveContext};
+    use crate::overworld::movement::{Movement, MovementProfile};
+    use crate::overworld::multiside::OneShotAnimation;
+    use crate::overworld::walk::WalkState;
+    use std::time::Duration;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    struct Walls {
+        bounds: OverworldRect<f32>,
+        walls: Vec<OverworldRect<f32>>,
+    }
+
+    impl PassMap for Walls {
+        fn collides_with(&self, rect: &OverworldRect<f32>) -> bool {
+            self.walls.iter().any(|wall| wall.intersects(rect))
+        }
+
+        fn bounds(&self) -> OverworldRect<f32> {
+            self.bounds
+        }
+    }
+
+    fn rect(x1: f32, y1: f32, x2: f32, y2: f32) -> OverworldRect<f32> {
+        OverworldRect::new([x1, y1].into(), [x2, y2].into())
+    }
+
+    fn room_with(walls: Vec<OverworldRect<f32>>) -> Walls {
+        Walls {
+            bounds: rect(0.0, 0.0, 200.0, 200.0),
+            walls,
+        }
+    }
+
+    fn agent_hitbox() -> OverworldRect<f32> {
+        rect(-4.0, -4.0, 4.0, 4.0)
+    }
+
+    struct TestAgent {
+        move_context: MoveContext,
+        walk_state: WalkState,
+        movement: Movement,
+    }
+
+    impl TestAgent {
+        fn at(x: f32, y: f32) -> Self {
+            Self {
+                move_context: MoveContext {
+                    position: Position::new(x, y),
+                    direction: Direction::Backward,
+                },
+                walk_state: WalkState::Still,
+                movement: Movement::new(MovementProfile::default()),
+            }
+        }
+    }
+
+    impl AsRef<MoveContext> for TestAgent {
+        fn as_ref(&self) -> &MoveContext {
+            &self.move_context
+        }
+    }
+
+    impl AsMut<MoveContext> for TestAgent {
+        fn as_mut(&mut self) -> &mut MoveContext {
+            &mut self.move_context
+        }
+    }
+
+    impl HasMoveContext for TestAgent {}
+
+    impl Walk for TestAgent {
+        fn walk_state(&self) -> WalkState {
+            self.walk_state
+        }
+
+        fn set_walk_state(&mut self, walk_state: WalkState) {
+            self.walk_state = walk_state
+        }
+
+        fn movement(&self) -> &Movement {
+            &self.movement
+        }
+
+        fn movement_mut(&mut self) -> &mut Movement {
+            &mut self.movement
+        }
+    }
+
+    impl Hitbox for TestAgent {
+        fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+            agent_hitbox()
+        }
+    }
+
+    impl PlayAnimation for TestAgent {
+        fn one_shot_animation(&self) -> Option<&OneShotAnimation> {
+            None
+        }
+
+        fn set_one_shot_animation(&mut self, _animation: Option<OneShotAnimation>) {}
+    }
+
+    // Runs the walk frame by frame, returning the outcome and every position the agent had.
+    fn simulate(
+        agent: &mut TestAgent,
+        waypoints: Vec<Position>,
+    ) -> (Result<(), NavigationError>, Vec<Position>) {
+        let mut walk = PathWalk::new(EntityId::Player, waypoints, None);
+        let mut trail = vec![agent.position()];
+        for _ in 0..10_000 {
+            if let Some(outcome) = walk.steer(agent) {
+                return (outcome, trail);
+            }
+            let position = agent.get_updated_position(FRAME);
+            agent.set_position(position);
+            trail.push(position);
+        }
+        panic!("The walk never finished");
+    }
+
+    #[test]
+    fn straight_path_when_line_is_clear() {
+        let pass_map = room_with(Vec::new());
+        let grid = NavGrid::new(&pass_map, agent_hitbox());
+        let to = Position::new(150.0, 120.0);
+        assert_eq!(grid.find_path(Position::new(20.0, 20.0), to), Ok(vec![to]));
+    }
+
+    #[test]
+    fn path_goes_around_walls() {
+        let pass_map = room_with(vec![rect(90.0, 0.0, 110.0, 150.0)]);
+        let grid = NavGrid::new(&pass_map, agent_hitbox());
+        let (from, to) = (Position::new(40.0, 40.0), Position::new(160.0, 40.0));
+        let path = grid.find_path(from, to).unwrap();
+
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.iter().any(|point| point.y > 150.0));
+        let legs = std::iter::once(&from).chain(&path).zip(&path);
+        for (a, b) in legs {
+            assert!(grid.is_line_clear(*a, *b), "{:?} -> {:?} is blocked", a, b);
+        }
+    }
+
+    #[test]
+    fn blocked_or_unreachable_targets_are_reported() {
+        let pass_map = room_with(vec![rect(90.0, 0.0, 110.0, 200.0)]);
+        let grid = NavGrid::new(&pass_map, agent_hitbox());
+        let from = Position::new(40.0, 40.0);
+        assert_eq!(
+            grid.find_path(from, Position::new(100.0, 40.0)),
+            Err(NavigationError::TargetBlocked)
+        );
+        assert_eq!(
+            grid.find_path(from, Position::new(160.0, 40.0)),
+            Err(NavigationError::NoPath)
+        );
+    }
+
+    #[test]
+    fn smoothing_skips_waypoints_in_plain_sight() {
+        let pass_map = room_with(vec![rect(90.0, 0.0, 110.0, 150.0)]);
+        let grid = NavGrid::new(&pass_map, agent_hitbox());
+        let line = (0..=10)
+            .map(|i| Position::new(20.0 + 5.0 * i as f32, 20.0))
+            .collect();
+        assert_eq!(grid.smooth(line), vec![Position::new(70.0, 20.0)]);
+
+        let corner = vec![
+            Position::new(40.0, 100.0),
+            Position::new(40.0, 170.0),
+            Position::new(100.0, 170.0),
+            Position::new(160.0, 170.0),
+            Position::new(160.0, 100.0),
+        ];
+        let smoothed = grid.smooth(corner);
+        assert_eq!(smoothed.last(), Some(&Position::new(160.0, 100.0)));
+        assert!(smoothed.len() >= 2, "{:?} cuts through the wall", smoothed);
+    }
+
+    #[test]
+    fn walk_arrives_by_walking() {
+        let mut agent = TestAgent::at(0.0, 0.0);
+        let target = Position::new(60.0, 40.0);
+        let (outcome, trail) = simulate(&mut agent, vec![Position::new(60.0, 0.0), target]);
+
+        assert_eq!(outcome, Ok(()));
+        assert!(trail.len() > 40, "arrived in {} frames", trail.len());
+        let max_step = MovementProfile::default().walk_speed * FRAME.as_secs_f32() + 1e-3;
+        for (a, b) in trail.iter().zip(&trail[1..]) {
+            assert!(
+                (*b - *a).length() <= max_step,
+                "jumped from {:?} to {:?}",
+                a,
+                b
+            );
+        }
+        assert!((agent.position() - target).length() <= ARRIVAL_DISTANCE);
+    }
+
+    #[test]
+    fn walk_turns_back_instead_of_skipping_waypoints() {
+        let mut agent = TestAgent::at(0.0, 0.0);
+        let waypoints = vec![Position::new(50.0, 0.0), Position::new(0.0, 0.0)];
+        let (outcome, trail) = simulate(&mut agent, waypoints);
+
+        assert_eq!(outcome, Ok(()));
+        assert!(trail.iter().any(|point| point.x >= 50.0 - ARRIVAL_DISTANCE));
+        assert!(agent.position().to_vector().length() <= ARRIVAL_DISTANCE);
+    }
+
+    #[test]
+    fn walk_reports_an_agent_that_cannot_move() {
+        let mut agent = TestAgent::at(0.0, 0.0);
+        let mut walk = PathWalk::new(EntityId::Player, vec![Position::new(50.0, 0.0)], None);
+        let outcome = (0..=STUCK_FRAMES).find_map(|_| walk.steer(&mut agent));
+        assert_eq!(outcome, Some(Err(NavigationError::Stuck)));
+    }
+}
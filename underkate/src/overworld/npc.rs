@@ -0,0 +1,154 @@
+use super::draw_order::{DrawLayer, YSorted};
+use super::hitbox::{Hitbox, Solid};
+use super::interaction::Interact;
+use super::move_trait::{Direction, HasMoveContext, MoveContext, Position};
+use super::movement::{Movement, MovementProfile};
+use super::multiside::{
+    DirectionalTexturePaths, DirectionalTextures, MoveAnimatedMultiside, OneShotAnimation,
+    PlayAnimation,
+};
+use super::walk::{Walk, WalkState};
+use crate::geometry::OverworldRect;
+use crate::graphics::texture::Texture;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use crate::script::rust_script::RustScript;
+use crate::script::Script;
+
+#[derive(Debug, Clone)]
+pub struct CreationParams {
+    pub name: String,
+    pub position: Position,
+    pub direction: Direction,
+    pub textures: DirectionalTexturePaths,
+    pub hitbox: OverworldRect<f32>,
+    pub solid: bool,
+    pub layer: DrawLayer,
+    pub movement: MovementProfile,
+    pub interaction_script: Option<&'static str>,
+}
+
+pub struct Npc {
+    name: String,
+    textures: DirectionalTextures,
+    hitbox: OverworldRect<f32>,
+    solid: bool,
+    layer: DrawLayer,
+    move_context: MoveContext,
+    walk_state: WalkState,
+    movement: Movement,
+    animation: Option<OneShotAnimation>,
+    interaction_script: Option<Box<dyn Script>>,
+}
+
+impl Npc {
+    pub fn new(params: &CreationParams, resources: &GlobalResourceStorage) -> Self {
+        let interaction_script = params.interaction_script.map(|name| {
+            let script: RustScript = resources.get_cloned(name);
+            Box::new(script) as Box<dyn Script>
+        });
+
+        Self {
+            name: params.name.clone(),
+            textures: DirectionalTextures::load(&params.textures, resources),
+            hitbox: params.hitbox,
+            solid: params.solid,
+            layer: params.layer,
+            move_context: MoveContext {
+                position: params.position,
+                direction: params.direction,
+            },
+            walk_state: WalkState::default(),
+            movement: Movement::new(params.movement),
+            animation: None,
+            interaction_script,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AsRef<MoveContext> for Npc {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Npc {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Npc {}
+
+impl PlayAnimation for Npc {
+    fn one_shot_animation(&self) -> Option<&OneShotAnimation> {
+        self.animation.as_ref()
+    }
+
+    fn set_one_shot_animation(&mut self, animation: Option<OneShotAnimation>) {
+        self.animation = animation
+    }
+}
+
+impl MoveAnimatedMultiside for Npc {
+    fn texture_for_direction(&self, direction: Direction) -> &Texture {
+        self.textures.for_direction(direction)
+    }
+
+    fn is_moving(&self) -> bool {
+        matches!(self.walk_state, WalkState::Walking(_))
+    }
+
+    fn direction(&self) -> Direction {
+        self.move_context.direction
+    }
+}
+
+impl Walk for Npc {
+    fn walk_state(&self) -> WalkState {
+        self.walk_state
+    }
+
+    fn set_walk_state(&mut self, walk_state: WalkState) {
+        self.walk_state = walk_state
+    }
+
+    fn movement(&self) -> &Movement {
+        &self.movement
+    }
+
+    fn movement_mut(&mut self) -> &mut Movement {
+        &mut self.movement
+    }
+}
+
+impl Hitbox for Npc {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.hitbox
+    }
+}
+
+impl Solid for Npc {
+    fn is_solid(&self) -> bool {
+        self.solid
+    }
+}
+
+impl Interact for Npc {
+    fn interaction_script_mut(&mut self) -> Option<&mut Box<dyn Script>> {
+        self.interaction_script.as_mut()
+    }
+
+    fn can_interact(&self) -> bool {
+        self.interaction_script.is_some()
+    }
+}
+
+impl YSorted for Npc {
+    fn draw_layer(&self) -> DrawLayer {
+        self.layer
+    }
+}
@@ -1,9 +1,58 @@
 pub mod bitmap_pass_map;
+pub mod composite_pass_map;
+pub mod polygon_pass_map;
+pub mod terrain;
 
 use crate::geometry::OverworldRect;
 pub use bitmap_pass_map::BitmapPassMap;
+pub use composite_pass_map::CompositePassMap;
+pub use polygon_pass_map::PolygonPassMap;
+pub use terrain::{TerrainClass, DEFAULT_TERRAIN};
 
 pub trait PassMap {
     fn collides_with(&self, rect: &OverworldRect<f32>) -> bool;
     fn bounds(&self) -> OverworldRect<f32>;
+
+    /// The terrain covering most of the free part of `rect`.
+    fn terrain_under(&self, _rect: &OverworldRect<f32>) -> &TerrainClass {
+        &DEFAULT_TERRAIN
+    }
+}
+
+/// Any of the pass maps which can be declared in `pass-map.toml`.
+#[derive(Clone)]
+pub enum AnyPassMap {
+    Bitmap(BitmapPassMap),
+    // `load_pass_map!` builds these for the manifests that declare them, and every pass map
+    // shipped so far is a bitmap.
+    #[allow(dead_code)]
+    Polygon(PolygonPassMap),
+    #[allow(dead_code)]
+    Composite(CompositePassMap),
+}
+
+impl PassMap for AnyPassMap {
+    fn collides_with(&self, rect: &OverworldRect<f32>) -> bool {
+        match self {
+            Self::Bitmap(pass_map) => pass_map.collides_with(rect),
+            Self::Polygon(pass_map) => pass_map.collides_with(rect),
+            Self::Composite(pass_map) => pass_map.collides_with(rect),
+        }
+    }
+
+    fn bounds(&self) -> OverworldRect<f32> {
+        match self {
+            Self::Bitmap(pass_map) => pass_map.bounds(),
+            Self::Polygon(pass_map) => pass_map.bounds(),
+            Self::Composite(pass_map) => pass_map.bounds(),
+        }
+    }
+
+    fn terrain_under(&self, rect: &OverworldRect<f32>) -> &TerrainClass {
+        match self {
+            Self::Bitmap(pass_map) => pass_map.terrain_under(rect),
+            Self::Polygon(pass_map) => pass_map.terrain_under(rect),
+            Self::Composite(pass_map) => pass_map.terrain_under(rect),
+        }
+    }
 }
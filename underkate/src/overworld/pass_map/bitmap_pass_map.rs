@@ -1,27 +1,73 @@
+use super::terrain::{TerrainClass, TerrainPalette};
 use super::PassMap;
 use crate::geometry::OverworldRect;
 
+// The occupancy table is stored in `u16`s if every count fits in one, and in `u32`s otherwise, the
+// way `load_pass_map!` generates it.
+fn table_entry_size(width: usize, height: usize) -> usize {
+    if width * height <= u16::MAX as usize {
+        std::mem::size_of::<u16>()
+    } else {
+        std::mem::size_of::<u32>()
+    }
+}
+
+/// `terrain_under` looks at no more than this many pixels along each side of the rect.
+const TERRAIN_SAMPLES_PER_SIDE: usize = 8;
+
+// Spreads up to `TERRAIN_SAMPLES_PER_SIDE` pixels evenly over the range, each in the middle of its
+// share of it.
+fn sample_range(range: std::ops::Range<usize>) -> impl Iterator<Item = usize> {
+    let len = range.end - range.start;
+    let samples = len.min(TERRAIN_SAMPLES_PER_SIDE);
+    (0..samples).map(move |sample| range.start + (2 * sample + 1) * len / (2 * samples))
+}
+
 #[derive(Clone, Copy)]
 pub struct Bitmap {
     width: usize,
     height: usize,
     data: &'static [u8],
+    /// Summed-area table of occupied pixels, `(width + 1) * (height + 1)` little-endian entries
+    /// of `table_entry_size` bytes each, generated by `load_pass_map!`.
+    occupancy_table: &'static [u8],
+    table_entry_size: usize,
+}
+
+fn check_size(name: &str, expected_size: usize, real_size: usize) {
+    if real_size != expected_size {
+        panic!(
+            "Size mismatch of {}: expected size {}, but the real size is {}",
+            name, expected_size, real_size
+        );
+    }
 }
 
 impl Bitmap {
-    pub fn new(width: usize, height: usize, data: &'static [u8]) -> Self {
+    pub fn new(
+        width: usize,
+        height: usize,
+        data: &'static [u8],
+        occupancy_table: &'static [u8],
+    ) -> Self {
         let expected_size = width.checked_mul(height).expect("Size overflow");
-        if data.len() != expected_size {
-            panic!(
-                "Size mismatch: expected size {}, but the real size is {}",
-                expected_size,
-                data.len()
-            );
-        }
+        check_size("bitmap", expected_size, data.len());
+        let table_entry_size = table_entry_size(width, height);
+        let expected_table_size = (width + 1)
+            .checked_mul(height + 1)
+            .and_then(|entries| entries.checked_mul(table_entry_size))
+            .expect("Size overflow");
+        check_size(
+            "occupancy table",
+            expected_table_size,
+            occupancy_table.len(),
+        );
         Self {
             width,
             height,
             data,
+            occupancy_table,
+            table_entry_size,
         }
     }
 
@@ -31,33 +77,36 @@ impl Bitmap {
             .expect("Size overflow")
     }
 
-    unsafe fn index_unchecked(&self, x: usize, y: usize) -> usize {
-        y * self.width + x
+    pub fn luma(&self, x: usize, y: usize) -> u8 {
+        self.data[self.index(x, y)]
     }
 
-    pub unsafe fn pixel_occupied_unchecked(&self, x: usize, y: usize) -> bool {
-        let index = self.index_unchecked(x, y);
-        *self.data.get_unchecked(index) == 0
+    // The number of occupied pixels above and to the left of the point.
+    unsafe fn occupied_before_unchecked(&self, x: usize, y: usize) -> u32 {
+        let offset = (y * (self.width + 1) + x) * self.table_entry_size;
+        match *self
+            .occupancy_table
+            .get_unchecked(offset..offset + self.table_entry_size)
+        {
+            [low, high] => u16::from_le_bytes([low, high]) as u32,
+            [b0, b1, b2, b3] => u32::from_le_bytes([b0, b1, b2, b3]),
+            _ => unreachable!("Occupancy table entries are either 2 or 4 bytes long"),
+        }
     }
 
     pub unsafe fn rect_occupied_unchecked(&self, rect: &OverworldRect<usize>) -> bool {
-        for y in rect.y_range() {
-            for x in rect.x_range() {
-                if self.pixel_occupied_unchecked(x, y) {
-                    return true;
-                }
-            }
-        }
-        false
+        let count = self.occupied_before_unchecked(rect.max.x, rect.max.y)
+            + self.occupied_before_unchecked(rect.min.x, rect.min.y)
+            - self.occupied_before_unchecked(rect.min.x, rect.max.y)
+            - self.occupied_before_unchecked(rect.max.x, rect.min.y);
+        count > 0
     }
 
     pub fn rect_occupied(&self, rect: &OverworldRect<usize>) -> bool {
         assert!(rect.min.x <= rect.max.x);
         assert!(rect.min.y <= rect.max.y);
-        let min_index = self.index(rect.min.x, rect.min.y);
-        let max_index = self.index(rect.max.x, rect.max.y);
-        assert!(min_index <= max_index);
-        assert!(max_index < self.data.len());
+        assert!(rect.max.x <= self.width);
+        assert!(rect.max.y <= self.height);
         unsafe { self.rect_occupied_unchecked(rect) }
     }
 
@@ -74,24 +123,219 @@ impl Bitmap {
 pub struct BitmapPassMap {
     bitmap: Bitmap,
     scale_recip: f32,
+    terrain: TerrainPalette,
 }
 
 impl BitmapPassMap {
-    pub fn new(bitmap: Bitmap, scale: f32) -> Self {
+    pub fn new(bitmap: Bitmap, scale: f32, terrain: TerrainPalette) -> Self {
         Self {
             bitmap,
             scale_recip: scale.recip(),
+            terrain,
         }
     }
+
+    fn pixel_rect(&self, rect: &OverworldRect<f32>) -> OverworldRect<usize> {
+        let scaled = rect.scale(self.scale_recip, self.scale_recip);
+        scaled.round().to_usize()
+    }
+
+    // Unlike `pixel_rect`, accepts rects sticking out of the bitmap or lying outside of it.
+    fn clamped_pixel_rect(&self, rect: &OverworldRect<f32>) -> Option<OverworldRect<usize>> {
+        let bounds = OverworldRect::from_size([self.bitmap.width(), self.bitmap.height()].into());
+        let scaled = rect.scale(self.scale_recip, self.scale_recip).round();
+        Some(scaled.intersection(&bounds.to_f32())?.to_usize())
+    }
 }
 
 impl PassMap for BitmapPassMap {
     fn collides_with(&self, rect: &OverworldRect<f32>) -> bool {
-        let scaled = rect.scale(self.scale_recip, self.scale_recip);
-        self.bitmap.rect_occupied(&scaled.round().to_usize())
+        self.bitmap.rect_occupied(&self.pixel_rect(rect))
     }
 
     fn bounds(&self) -> OverworldRect<f32> {
-        OverworldRect::from_size([self.bitmap.width(), self.bitmap.height()].into()).to_f32()
+        let scale = self.scale_recip.recip();
+        OverworldRect::from_size([self.bitmap.width(), self.bitmap.height()].into())
+            .to_f32()
+            .scale(scale, scale)
+    }
+
+    // Solid pixels don't vote; ties go to the class declared first. Large rects are sampled, so
+    // this takes the same time for every entity.
+    fn terrain_under(&self, rect: &OverworldRect<f32>) -> &TerrainClass {
+        let rect = match self.clamped_pixel_rect(rect) {
+            Some(rect) => rect,
+            None => return self.terrain.class(0),
+        };
+        let mut votes = [0u8; 256];
+        for y in sample_range(rect.y_range()) {
+            for x in sample_range(rect.x_range()) {
+                match self.bitmap.luma(x, y) {
+                    0 => {}
+                    luma => votes[self.terrain.class_index(luma)] += 1,
+                }
+            }
+        }
+        let mut winner = 0;
+        for (index, &count) in votes[..self.terrain.len()].iter().enumerate() {
+            if count > votes[winner] {
+                winner = index;
+            }
+        }
+        self.terrain.class(winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sample_range, Bitmap, BitmapPassMap, TERRAIN_SAMPLES_PER_SIDE};
+    use crate::geometry::OverworldRect;
+    use crate::overworld::pass_map::{AnyPassMap, PassMap};
+    use underkate_tools::load_pass_map;
+
+    // The way occupancy used to be checked, before the summed-area table.
+    fn rect_occupied_by_scan(bitmap: &Bitmap, rect: &OverworldRect<usize>) -> bool {
+        rect.y_range()
+            .any(|y| rect.x_range().any(|x| bitmap.data[bitmap.index(x, y)] == 0))
+    }
+
+    // The way terrain used to be found, by letting every free pixel vote.
+    fn terrain_by_scan(pass_map: &BitmapPassMap, rect: &OverworldRect<usize>) -> &'static str {
+        let mut votes = vec![0usize; pass_map.terrain.len()];
+        for y in rect.y_range() {
+            for x in rect.x_range() {
+                match pass_map.bitmap.luma(x, y) {
+                    0 => {}
+                    luma => votes[pass_map.terrain.class_index(luma)] += 1,
+                }
+            }
+        }
+        let mut winner = 0;
+        for (index, &count) in votes.iter().enumerate() {
+            if count > votes[winner] {
+                winner = index;
+            }
+        }
+        pass_map.terrain.class(winner).name
+    }
+
+    fn terrain_test_pass_map() -> BitmapPassMap {
+        match load_pass_map!("test/terrain") {
+            AnyPassMap::Bitmap(pass_map) => pass_map,
+            _ => panic!("The terrain test pass map is expected to be a bitmap"),
+        }
+    }
+
+    fn home_room_bitmap() -> Bitmap {
+        match load_pass_map!("overworld/rooms/home/room") {
+            AnyPassMap::Bitmap(pass_map) => pass_map.bitmap,
+            _ => panic!("The home room pass map is expected to be a bitmap"),
+        }
+    }
+
+    #[test]
+    fn table_matches_scan() {
+        let bitmap = home_room_bitmap();
+        for &(width, height) in &[(0, 0), (1, 1), (3, 7), (20, 10), (37, 52)] {
+            for y in (0..=bitmap.height() - height).step_by(9) {
+                for x in (0..=bitmap.width() - width).step_by(7) {
+                    let rect = OverworldRect::new([x, y].into(), [x + width, y + height].into());
+                    assert_eq!(
+                        bitmap.rect_occupied(&rect),
+                        rect_occupied_by_scan(&bitmap, &rect),
+                        "Mismatch at {:?}",
+                        rect,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_byte_table_matches_scan() {
+        let bitmap = terrain_test_pass_map().bitmap;
+        for min_y in 0..=bitmap.height() {
+            for max_y in min_y..=bitmap.height() {
+                for min_x in 0..=bitmap.width() {
+                    for max_x in min_x..=bitmap.width() {
+                        let rect = OverworldRect::new([min_x, min_y].into(), [max_x, max_y].into());
+                        assert_eq!(
+                            bitmap.rect_occupied(&rect),
+                            rect_occupied_by_scan(&bitmap, &rect),
+                            "Mismatch at {:?}",
+                            rect,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn whole_bitmap_and_single_pixels_match_scan() {
+        let bitmap = home_room_bitmap();
+        let whole = OverworldRect::from_size([bitmap.width(), bitmap.height()].into());
+        assert_eq!(
+            bitmap.rect_occupied(&whole),
+            rect_occupied_by_scan(&bitmap, &whole)
+        );
+        for y in (0..bitmap.height()).step_by(3) {
+            for x in 0..bitmap.width() {
+                let pixel = OverworldRect::new([x, y].into(), [x + 1, y + 1].into());
+                assert_eq!(
+                    bitmap.rect_occupied(&pixel),
+                    rect_occupied_by_scan(&bitmap, &pixel)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn samples_are_spread_within_the_range() {
+        for start in 0..3 {
+            for end in start..start + 40 {
+                let samples: Vec<_> = sample_range(start..end).collect();
+                assert_eq!(samples.len(), (end - start).min(TERRAIN_SAMPLES_PER_SIDE));
+                assert!(samples.windows(2).all(|pair| pair[0] < pair[1]));
+                assert!(samples.iter().all(|sample| (start..end).contains(sample)));
+            }
+        }
+    }
+
+    #[test]
+    fn terrain_of_small_rects_matches_scan() {
+        let pass_map = terrain_test_pass_map();
+        let (width, height) = (pass_map.bitmap.width(), pass_map.bitmap.height());
+        let side = TERRAIN_SAMPLES_PER_SIDE;
+        for min_y in 0..height {
+            for max_y in min_y + 1..=(min_y + side).min(height) {
+                for min_x in 0..width {
+                    for max_x in min_x + 1..=(min_x + side).min(width) {
+                        let rect = OverworldRect::new([min_x, min_y].into(), [max_x, max_y].into());
+                        assert_eq!(
+                            pass_map.terrain_under(&rect.to_f32().scale(2.0, 2.0)).name,
+                            terrain_by_scan(&pass_map, &rect),
+                            "Mismatch at {:?}",
+                            rect,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn terrain_under_rects_sticking_out_of_the_bitmap() {
+        let pass_map = terrain_test_pass_map();
+        let terrain_at = |min: [f32; 2], max: [f32; 2]| {
+            pass_map
+                .terrain_under(&OverworldRect::new(min.into(), max.into()))
+                .name
+        };
+        assert_eq!(terrain_at([-10.0, -10.0], [6.0, 6.0]), "default");
+        assert_eq!(terrain_at([20.0, -50.0], [26.0, 100.0]), "water");
+        assert_eq!(terrain_at([-30.0, 24.0], [10.0, 80.0]), "sand");
+        assert_eq!(terrain_at([-30.0, -30.0], [-10.0, -10.0]), "default");
+        assert_eq!(terrain_at([100.0, 100.0], [120.0, 120.0]), "default");
     }
 }
@@ -0,0 +1,52 @@
+use super::{AnyPassMap, PassMap, TerrainClass, DEFAULT_TERRAIN};
+use crate::geometry::OverworldRect;
+
+/// A union of several pass maps: a rect collides if it collides with any of them.
+#[derive(Clone)]
+pub struct CompositePassMap {
+    sources: Vec<AnyPassMap>,
+    bounds: OverworldRect<f32>,
+}
+
+impl CompositePassMap {
+    // Called by the code `load_pass_map!` generates for `sources = [...]`.
+    #[allow(dead_code)]
+    pub fn new(sources: Vec<AnyPassMap>) -> Self {
+        let bounds = sources
+            .iter()
+            .map(PassMap::bounds)
+            .reduce(|a, b| a.union(&b))
+            .expect("A composite pass map needs at least one source");
+        Self { sources, bounds }
+    }
+}
+
+impl PassMap for CompositePassMap {
+    fn collides_with(&self, rect: &OverworldRect<f32>) -> bool {
+        // Sources do not have to cover the whole union, and the parts they don't cover are free.
+        self.sources.iter().any(|source| {
+            source
+                .bounds()
+                .intersection(rect)
+                .is_some_and(|part| source.collides_with(&part))
+        })
+    }
+
+    fn bounds(&self) -> OverworldRect<f32> {
+        self.bounds
+    }
+
+    // A source with a special terrain under the rect wins over the ones with the default terrain.
+    fn terrain_under(&self, rect: &OverworldRect<f32>) -> &TerrainClass {
+        let mut terrains = self
+            .sources
+            .iter()
+            .filter(|source| source.bounds().intersects(rect))
+            .map(|source| source.terrain_under(rect));
+        let first = terrains.next().unwrap_or(&DEFAULT_TERRAIN);
+        std::iter::once(first)
+            .chain(terrains)
+            .find(|terrain| terrain.name != DEFAULT_TERRAIN.name)
+            .unwrap_or(first)
+    }
+}
@@ -0,0 +1,115 @@
+use super::PassMap;
+use crate::geometry::{OverworldPoint, OverworldRect};
+
+/// A solid area bounded by a closed polyline. It does not have to be convex.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    vertices: Vec<OverworldPoint<f32>>,
+    bounding_box: OverworldRect<f32>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<OverworldPoint<f32>>) -> Self {
+        assert!(vertices.len() >= 3, "A polygon needs at least 3 vertices");
+        let bounding_box = OverworldRect::from_points(&vertices);
+        Self {
+            vertices,
+            bounding_box,
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (OverworldPoint<f32>, OverworldPoint<f32>)> + '_ {
+        let next_vertices = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next_vertices.copied())
+    }
+
+    // Even-odd rule.
+    fn contains(&self, point: OverworldPoint<f32>) -> bool {
+        self.edges()
+            .filter(|(a, b)| (a.y > point.y) != (b.y > point.y))
+            .filter(|(a, b)| {
+                let crossing_x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                point.x < crossing_x
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    pub fn intersects(&self, rect: &OverworldRect<f32>) -> bool {
+        if !self.bounding_box.intersects(rect) {
+            return false;
+        }
+        // Either the outline crosses the rect, or the rect lies entirely inside the polygon.
+        self.edges()
+            .any(|(a, b)| segment_intersects_rect(a, b, rect))
+            || self.contains(rect.center())
+    }
+}
+
+// Liang-Barsky clipping; touching the border of the rect does not count as intersecting it.
+fn segment_intersects_rect(
+    a: OverworldPoint<f32>,
+    b: OverworldPoint<f32>,
+    rect: &OverworldRect<f32>,
+) -> bool {
+    let delta = b - a;
+    let constraints = [
+        (-delta.x, a.x - rect.min.x),
+        (delta.x, rect.max.x - a.x),
+        (-delta.y, a.y - rect.min.y),
+        (delta.y, rect.max.y - a.y),
+    ];
+    let mut enter = 0.0_f32;
+    let mut leave = 1.0_f32;
+    for &(direction, distance) in constraints.iter() {
+        if direction == 0.0 {
+            if distance <= 0.0 {
+                return false;
+            }
+        } else {
+            let t = distance / direction;
+            if direction < 0.0 {
+                enter = enter.max(t);
+            } else {
+                leave = leave.min(t);
+            }
+        }
+    }
+    enter < leave
+}
+
+/// A pass map made of solid rects and polygons, which does not depend on any resolution.
+#[derive(Debug, Clone)]
+pub struct PolygonPassMap {
+    bounds: OverworldRect<f32>,
+    rects: Vec<OverworldRect<f32>>,
+    polygons: Vec<Polygon>,
+}
+
+impl PolygonPassMap {
+    // Called by the code `load_pass_map!` generates for pass maps made of shapes.
+    #[allow(dead_code)]
+    pub fn new(
+        bounds: OverworldRect<f32>,
+        rects: Vec<OverworldRect<f32>>,
+        polygons: Vec<Polygon>,
+    ) -> Self {
+        Self {
+            bounds,
+            rects,
+            polygons,
+        }
+    }
+}
+
+impl PassMap for PolygonPassMap {
+    fn collides_with(&self, rect: &OverworldRect<f32>) -> bool {
+        self.rects.iter().any(|solid| solid.intersects(rect))
+            || self.polygons.iter().any(|polygon| polygon.intersects(rect))
+    }
+
+    fn bounds(&self) -> OverworldRect<f32> {
+        self.bounds
+    }
+}
@@ -0,0 +1,59 @@
+/// A kind of ground, such as grass or shallow water, which entities can walk on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainClass {
+    pub name: &'static str,
+    /// Multiplies the speed of everything walking on it.
+    pub speed_factor: f32,
+    /// Named events raised when the player steps onto or off the terrain.
+    pub enter_event: Option<&'static str>,
+    pub leave_event: Option<&'static str>,
+    /// The sound of the player's steps.
+    pub footsteps: Option<&'static str>,
+}
+
+pub const DEFAULT_TERRAIN: TerrainClass = TerrainClass {
+    name: "default",
+    speed_factor: 1.0,
+    enter_event: None,
+    leave_event: None,
+    footsteps: None,
+};
+
+/// Maps pass map luma values to terrain classes. The first class is the default one, which
+/// covers every free pixel not claimed by another class.
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainPalette {
+    classes: &'static [TerrainClass],
+    class_by_luma: &'static [u8; 256],
+}
+
+impl TerrainPalette {
+    pub fn new(classes: &'static [TerrainClass], class_by_luma: &'static [u8; 256]) -> Self {
+        assert!(
+            !classes.is_empty(),
+            "A terrain palette needs a default class"
+        );
+        assert!(
+            class_by_luma
+                .iter()
+                .all(|&index| (index as usize) < classes.len()),
+            "Terrain class index out of range"
+        );
+        Self {
+            classes,
+            class_by_luma,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn class_index(&self, luma: u8) -> usize {
+        self.class_by_luma[luma as usize] as usize
+    }
+
+    pub fn class(&self, index: usize) -> &TerrainClass {
+        &self.classes[index]
+    }
+}
@@ -5,6 +5,12 @@ pub trait PassabilityCheck {
     fn can_pass(&self, player_hitbox: &OverworldRect<f32>) -> bool;
 }
 
+impl<T: PassabilityCheck + ?Sized> PassabilityCheck for &T {
+    fn can_pass(&self, player_hitbox: &OverworldRect<f32>) -> bool {
+        (**self).can_pass(player_hitbox)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PassMapPassabilityChecker<'passmap, P: PassMap> {
     pass_map: &'passmap P,
@@ -23,3 +29,31 @@ impl<P: PassMap> PassabilityCheck for PassMapPassabilityChecker<'_, P> {
         within_rect() && map_passable()
     }
 }
+
+/// Extends another checker with the hitboxes of solid entities, such as NPCs or props.
+#[derive(Debug, Clone)]
+pub struct EntityPassabilityChecker<P: PassabilityCheck> {
+    inner: P,
+    solid_hitboxes: Vec<OverworldRect<f32>>,
+}
+
+impl<P: PassabilityCheck> EntityPassabilityChecker<P> {
+    pub fn new(inner: P, solid_hitboxes: impl IntoIterator<Item = OverworldRect<f32>>) -> Self {
+        Self {
+            inner,
+            solid_hitboxes: solid_hitboxes.into_iter().collect(),
+        }
+    }
+}
+
+impl<P: PassabilityCheck> PassabilityCheck for EntityPassabilityChecker<P> {
+    fn can_pass(&self, player_hitbox: &OverworldRect<f32>) -> bool {
+        let entities_passable = || {
+            !self
+                .solid_hitboxes
+                .iter()
+                .any(|solid| solid.intersects(player_hitbox))
+        };
+        self.inner.can_pass(player_hitbox) && entities_passable()
+    }
+}
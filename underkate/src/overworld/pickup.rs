@@ -0,0 +1,115 @@
+use super::draw_order::{DrawLayer, YSorted};
+use super::hitbox::Hitbox;
+use super::move_trait::{Direction, HasMoveContext, MoveContext, Position};
+use crate::dialog::DialogFrame;
+use crate::flags::Flags;
+use crate::geometry::{OverworldRect, ScreenPoint};
+use crate::graphics::texture::Texture;
+use crate::graphics::Draw;
+use crate::inventory::Inventory;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use ggez::{Context, GameResult};
+
+/// Shown instead of the pickup text when the player can't carry any more items.
+const INVENTORY_FULL_TEXT: &str = "overworld.inventory_full";
+/// Follows the pickup text, telling how many items the player carries now.
+const ITEMS_CARRIED_TEXT: &str = "overworld.items_carried";
+
+#[derive(Debug, Clone)]
+pub struct PickupCreationParams {
+    pub item: &'static str,
+    pub position: Position,
+    pub hitbox: OverworldRect<f32>,
+    pub texture: String,
+    /// Set once the item is picked up, so that it doesn't appear again.
+    pub flag: &'static str,
+    /// The string ID of the dialog shown when the item is picked up.
+    pub text: Option<&'static str>,
+}
+
+/// An item lying on the floor, which the player can pick up.
+pub struct Pickup {
+    item: &'static str,
+    texture: Texture,
+    hitbox: OverworldRect<f32>,
+    flag: &'static str,
+    text: Option<&'static str>,
+    move_context: MoveContext,
+    collected: bool,
+}
+
+impl Pickup {
+    pub fn new(params: &PickupCreationParams, resources: &GlobalResourceStorage) -> Self {
+        Self {
+            item: params.item,
+            texture: resources.get_cloned(&params.texture),
+            hitbox: params.hitbox,
+            flag: params.flag,
+            text: params.text,
+            move_context: MoveContext {
+                position: params.position,
+                direction: Direction::Backward,
+            },
+            collected: false,
+        }
+    }
+
+    pub fn is_collected(&self) -> bool {
+        self.collected
+    }
+
+    /// Hides the item if it has been picked up, possibly on an earlier visit.
+    pub fn update(&mut self, flags: &Flags) {
+        self.collected = flags.is_set(self.flag);
+    }
+
+    /// Puts the item into the inventory if there is room for it, and returns what to tell the
+    /// player about it.
+    pub fn pick_up(&mut self, inventory: &mut Inventory, flags: &mut Flags) -> Vec<DialogFrame> {
+        if !inventory.add(self.item) {
+            return vec![DialogFrame::new(None, INVENTORY_FULL_TEXT)];
+        }
+        flags.raise(self.flag);
+        self.collected = true;
+        let carried = inventory.items().count() as i64;
+        let mut frames: Vec<_> = self
+            .text
+            .iter()
+            .map(|text_id| DialogFrame::new(None, text_id))
+            .collect();
+        frames.push(DialogFrame::with_count(None, ITEMS_CARRIED_TEXT, carried));
+        frames
+    }
+}
+
+impl AsRef<MoveContext> for Pickup {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Pickup {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Pickup {}
+
+impl Hitbox for Pickup {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.hitbox
+    }
+}
+
+impl Draw for Pickup {
+    fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult {
+        self.texture.draw(ctx, center_at)
+    }
+}
+
+impl YSorted for Pickup {
+    fn draw_layer(&self) -> DrawLayer {
+        DrawLayer::Below
+    }
+}
@@ -1,34 +1,48 @@
-use super::hitbox::Hitbox;
+use super::draw_order::YSorted;
+use super::hitbox::{Hitbox, Solid};
 use super::move_trait::{Direction, HasMoveContext, MoveContext};
-use super::multiside::MoveAnimatedMultiside;
+use super::movement::{Movement, MovementProfile};
+use super::multiside::{
+    DirectionalTexturePaths, DirectionalTextures, MoveAnimatedMultiside, OneShotAnimation,
+    PlayAnimation,
+};
 use super::multiwalk::{MultiWalk, MultiWalkState};
 use super::walk::{Walk, WalkState};
 use crate::geometry::OverworldRect;
 use crate::graphics::texture::Texture;
-use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use crate::resources::GlobalResourceStorage;
 
-const WALK_VELOCITY_ABS: f32 = 200.0;
+const MOVEMENT_PROFILE: MovementProfile = MovementProfile {
+    walk_speed: 200.0,
+    run_speed: 320.0,
+    acceleration: None,
+    deceleration: None,
+};
 
 pub struct Player {
-    front_texture: Texture,
-    back_texture: Texture,
-    leftward_texture: Texture,
-    rightward_texture: Texture,
+    textures: DirectionalTextures,
     move_context: MoveContext,
     walk_state: WalkState,
     multi_walk_state: MultiWalkState,
+    movement: Movement,
+    animation: Option<OneShotAnimation>,
 }
 
 impl Player {
     pub fn new(resources: &GlobalResourceStorage, move_context: MoveContext) -> Self {
+        let texture_paths = DirectionalTexturePaths {
+            left: String::from("overworld/player/leftward"),
+            right: String::from("overworld/player/rightward"),
+            forward: String::from("overworld/player/back"),
+            backward: String::from("overworld/player/front"),
+        };
         Self {
-            front_texture: resources.get_cloned("overworld/player/front"),
-            back_texture: resources.get_cloned("overworld/player/back"),
-            leftward_texture: resources.get_cloned("overworld/player/leftward"),
-            rightward_texture: resources.get_cloned("overworld/player/rightward"),
+            textures: DirectionalTextures::load(&texture_paths, resources),
             move_context,
             walk_state: WalkState::default(),
-            multi_walk_state: MultiWalkState::new(WALK_VELOCITY_ABS),
+            multi_walk_state: MultiWalkState::new(),
+            movement: Movement::new(MOVEMENT_PROFILE),
+            animation: None,
         }
     }
 }
@@ -47,18 +61,23 @@ impl AsMut<MoveContext> for Player {
 
 impl HasMoveContext for Player {}
 
+impl PlayAnimation for Player {
+    fn one_shot_animation(&self) -> Option<&OneShotAnimation> {
+        self.animation.as_ref()
+    }
+
+    fn set_one_shot_animation(&mut self, animation: Option<OneShotAnimation>) {
+        self.animation = animation
+    }
+}
+
 impl MoveAnimatedMultiside for Player {
     fn texture_for_direction(&self, direction: Direction) -> &Texture {
-        match direction {
-            Direction::Backward => &self.front_texture,
-            Direction::Forward => &self.back_texture,
-            Direction::Left => &self.leftward_texture,
-            Direction::Right => &self.rightward_texture,
-        }
+        self.textures.for_direction(direction)
     }
 
     fn is_moving(&self) -> bool {
-        !self.multi_walk_state.is_still()
+        matches!(self.walk_state, WalkState::Walking(_))
     }
 
     fn direction(&self) -> Direction {
@@ -74,6 +93,14 @@ impl Walk for Player {
     fn set_walk_state(&mut self, walk_state: WalkState) {
         self.walk_state = walk_state
     }
+
+    fn movement(&self) -> &Movement {
+        &self.movement
+    }
+
+    fn movement_mut(&mut self) -> &mut Movement {
+        &mut self.movement
+    }
 }
 
 impl MultiWalk for Player {
@@ -91,3 +118,11 @@ impl Hitbox for Player {
         OverworldRect::new([-16.0, -28.0].into(), [16.0, 36.0].into())
     }
 }
+
+impl Solid for Player {
+    fn is_solid(&self) -> bool {
+        true
+    }
+}
+
+impl YSorted for Player {}
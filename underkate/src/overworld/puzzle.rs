@@ -0,0 +1,437 @@
+use super::draw_order::{DrawLayer, YSorted};
+use super::hitbox::{Hitbox, Solid};
+use super::move_trait::{Direction, HasMoveContext, MoveContext, Position};
+use super::multiside::OneShotAnimation;
+use super::passability_checker::PassabilityCheck;
+use crate::flags::{self, Condition, Flags};
+use crate::geometry::{OverworldRect, ScreenPoint};
+use crate::graphics::texture::Texture;
+use crate::graphics::Draw;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use ggez::{Context, GameResult};
+use std::time::Duration;
+
+/// How long the player has to walk into a block before it moves.
+const PUSH_DELAY: Duration = Duration::from_millis(250);
+
+/// How fast a pushed block slides to the next grid cell, in overworld units per second.
+const SLIDE_SPEED: f32 = 96.0;
+
+/// Spikes add the damage they deal to this flag. There is no health yet, so this is how scripts
+/// and battles can tell how hurt the player is.
+pub const DAMAGE_TAKEN_FLAG: &str = "player.damage_taken";
+
+#[derive(Debug, Clone)]
+pub struct BlockCreationParams {
+    pub name: String,
+    pub position: Position,
+    pub texture: String,
+    /// The size of the grid cells. The block fills one cell and moves by one cell per push.
+    pub grid: f32,
+}
+
+/// A block which the player can push around, one grid cell at a time.
+pub struct Block {
+    name: String,
+    texture: Texture,
+    grid: f32,
+    move_context: MoveContext,
+    // Where the block is sliding to, if it has been pushed.
+    slide_target: Option<Position>,
+    // How long the player has been walking into the block.
+    pushed_for: Duration,
+}
+
+impl Block {
+    pub fn new(params: &BlockCreationParams, resources: &GlobalResourceStorage) -> Self {
+        let mut block = Self {
+            name: params.name.clone(),
+            texture: resources.get_cloned(&params.texture),
+            grid: params.grid,
+            move_context: MoveContext {
+                position: params.position,
+                direction: Direction::Backward,
+            },
+            slide_target: None,
+            pushed_for: Duration::ZERO,
+        };
+        block.place_at(params.position);
+        block
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Where the block stands, or will stand once it stops sliding.
+    pub fn resting_position(&self) -> Position {
+        self.slide_target.unwrap_or(self.move_context.position)
+    }
+
+    /// Moves the block to the center of the grid cell containing `position`.
+    pub fn place_at(&mut self, position: Position) {
+        let snap = |value: f32| ((value / self.grid).floor() + 0.5) * self.grid;
+        self.move_context.position = Position::new(snap(position.x), snap(position.y));
+        self.slide_target = None;
+        self.pushed_for = Duration::ZERO;
+    }
+
+    /// Continues sliding, or starts sliding once the block has been pushed in the same direction
+    /// for long enough. The block only moves where `pass` lets it: it doesn't start sliding
+    /// unless the next cell is free, and waits if something steps into its way while sliding.
+    pub fn update(
+        &mut self,
+        pushed: Option<Direction>,
+        time_slice: Duration,
+        pass: &impl PassabilityCheck,
+    ) {
+        if let Some(target) = self.slide_target {
+            let step = SLIDE_SPEED * time_slice.as_secs_f32();
+            let remaining = target - self.move_context.position;
+            let next = if remaining.length() <= step {
+                target
+            } else {
+                self.move_context.position + remaining.normalize() * step
+            };
+            if pass.can_pass(&self.hitbox_at(next)) {
+                self.move_context.position = next;
+                if next == target {
+                    self.slide_target = None;
+                }
+            }
+            return;
+        }
+
+        let direction = match pushed {
+            Some(direction) if direction == self.move_context.direction => direction,
+            Some(direction) => {
+                self.move_context.direction = direction;
+                self.pushed_for = Duration::ZERO;
+                return;
+            }
+            None => {
+                self.pushed_for = Duration::ZERO;
+                return;
+            }
+        };
+        self.pushed_for += time_slice;
+        if self.pushed_for < PUSH_DELAY {
+            return;
+        }
+        self.pushed_for = Duration::ZERO;
+        let target = self.move_context.position + direction.to_vector() * self.grid;
+        if pass.can_pass(&self.hitbox_at(target)) {
+            self.slide_target = Some(target);
+        }
+    }
+}
+
+impl AsRef<MoveContext> for Block {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Block {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Block {}
+
+impl Hitbox for Block {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        let half = self.grid / 2.0;
+        OverworldRect::new([-half, -half].into(), [half, half].into())
+    }
+}
+
+impl Solid for Block {
+    fn is_solid(&self) -> bool {
+        true
+    }
+}
+
+impl Draw for Block {
+    fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult {
+        self.texture.draw(ctx, center_at)
+    }
+}
+
+impl YSorted for Block {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwitchKind {
+    /// Pressed while the player or a block stands on it.
+    // Switches and gates are only declared in manifests, and no shipped room has a puzzle yet.
+    #[allow(dead_code)]
+    Floor { sticky: bool },
+    /// Toggled when the player interacts with it.
+    Lever,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchCreationParams {
+    pub kind: SwitchKind,
+    pub position: Position,
+    pub hitbox: OverworldRect<f32>,
+    pub flag: &'static str,
+    pub off_texture: String,
+    pub on_texture: String,
+}
+
+/// A switch controlling a flag. It shows whether the flag is set, no matter what set it.
+pub struct Switch {
+    kind: SwitchKind,
+    hitbox: OverworldRect<f32>,
+    flag: &'static str,
+    off_texture: Texture,
+    on_texture: Texture,
+    move_context: MoveContext,
+    on: bool,
+    // Whether something stood on a floor switch during the previous update.
+    pressed: bool,
+}
+
+impl Switch {
+    pub fn new(params: &SwitchCreationParams, resources: &GlobalResourceStorage) -> Self {
+        Self {
+            kind: params.kind,
+            hitbox: params.hitbox,
+            flag: params.flag,
+            off_texture: resources.get_cloned(&params.off_texture),
+            on_texture: resources.get_cloned(&params.on_texture),
+            move_context: MoveContext {
+                position: params.position,
+                direction: Direction::Backward,
+            },
+            on: false,
+            pressed: false,
+        }
+    }
+
+    pub fn is_lever(&self) -> bool {
+        self.kind == SwitchKind::Lever
+    }
+
+    /// Updates a floor switch from the hitboxes which can press it, and the look of any switch
+    /// from its flag.
+    pub fn update<'a>(
+        &mut self,
+        pressing: impl IntoIterator<Item = &'a OverworldRect<f32>>,
+        flags: &mut Flags,
+    ) {
+        if let SwitchKind::Floor { sticky } = self.kind {
+            let own_hitbox = self.hitbox_at(self.move_context.position);
+            let pressed = pressing
+                .into_iter()
+                .any(|hitbox| hitbox.intersects(&own_hitbox));
+            if pressed != self.pressed && (pressed || !sticky) {
+                flags.set(self.flag, pressed as i64);
+            }
+            self.pressed = pressed;
+        }
+        self.on = flags.is_set(self.flag);
+    }
+
+    pub fn toggle(&mut self, flags: &mut Flags) {
+        self.on = !flags.is_set(self.flag);
+        flags.set(self.flag, self.on as i64);
+    }
+}
+
+impl AsRef<MoveContext> for Switch {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Switch {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Switch {}
+
+impl Hitbox for Switch {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.hitbox
+    }
+}
+
+impl Solid for Switch {
+    fn is_solid(&self) -> bool {
+        self.is_lever()
+    }
+}
+
+impl Draw for Switch {
+    fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult {
+        if self.on {
+            self.on_texture.draw(ctx, center_at)
+        } else {
+            self.off_texture.draw(ctx, center_at)
+        }
+    }
+}
+
+impl YSorted for Switch {
+    fn draw_layer(&self) -> DrawLayer {
+        match self.kind {
+            SwitchKind::Floor { .. } => DrawLayer::Below,
+            SwitchKind::Lever => DrawLayer::Sorted,
+        }
+    }
+}
+
+// See `SwitchKind`.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GateKind {
+    Door,
+    /// Drawn below the other entities. Walking into them while they are up deals `damage`.
+    Spikes {
+        damage: i64,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct GateTexturePaths {
+    pub closed: String,
+    pub open: String,
+    /// Played once when the gate opens.
+    pub opening: Option<String>,
+    /// Played once when the gate closes.
+    pub closing: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GateCreationParams {
+    pub kind: GateKind,
+    pub position: Position,
+    pub hitbox: OverworldRect<f32>,
+    pub textures: GateTexturePaths,
+    /// The gate is open while all of these hold.
+    pub open_when: Vec<Condition>,
+}
+
+/// A door or spikes, which can be passed only while open.
+pub struct Gate {
+    kind: GateKind,
+    hitbox: OverworldRect<f32>,
+    closed_texture: Texture,
+    open_texture: Texture,
+    opening_texture: Option<Texture>,
+    closing_texture: Option<Texture>,
+    open_when: Vec<Condition>,
+    move_context: MoveContext,
+    // `None` until the first update, which opens or closes the gate without animation.
+    open: Option<bool>,
+    animation: Option<OneShotAnimation>,
+}
+
+impl Gate {
+    pub fn new(params: &GateCreationParams, resources: &GlobalResourceStorage) -> Self {
+        let load = |path: &String| -> Texture { resources.get_cloned(path) };
+        Self {
+            kind: params.kind,
+            hitbox: params.hitbox,
+            closed_texture: load(&params.textures.closed),
+            open_texture: load(&params.textures.open),
+            opening_texture: params.textures.opening.as_ref().map(load),
+            closing_texture: params.textures.closing.as_ref().map(load),
+            open_when: params.open_when.clone(),
+            move_context: MoveContext {
+                position: params.position,
+                direction: Direction::Backward,
+            },
+            open: None,
+            animation: None,
+        }
+    }
+
+    /// Opens or closes the gate according to the flags. A gate doesn't close on something
+    /// standing in it; it waits until it is free instead.
+    pub fn update(&mut self, flags: &Flags, occupied: bool) {
+        let should_open = flags::all_hold(&self.open_when, flags);
+        match self.open {
+            None => self.open = Some(should_open),
+            Some(open) if open == should_open => (),
+            Some(_) if !should_open && occupied => (),
+            Some(_) => {
+                self.open = Some(should_open);
+                let transition = if should_open {
+                    &self.opening_texture
+                } else {
+                    &self.closing_texture
+                };
+                self.animation = transition.as_ref().map(OneShotAnimation::start);
+            }
+        }
+        if self
+            .animation
+            .as_ref()
+            .is_some_and(OneShotAnimation::is_finished)
+        {
+            self.animation = None;
+        }
+    }
+
+    /// The damage dealt to the player walking into the gate, if it hurts at the moment.
+    pub fn damage(&self) -> Option<i64> {
+        match self.kind {
+            GateKind::Spikes { damage } if self.open != Some(true) => Some(damage),
+            _ => None,
+        }
+    }
+}
+
+impl AsRef<MoveContext> for Gate {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Gate {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Gate {}
+
+impl Hitbox for Gate {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.hitbox
+    }
+}
+
+impl Solid for Gate {
+    fn is_solid(&self) -> bool {
+        self.open != Some(true)
+    }
+}
+
+impl Draw for Gate {
+    fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult {
+        if let Some(animation) = &self.animation {
+            return animation.texture().draw(ctx, center_at);
+        }
+        if self.open == Some(true) {
+            self.open_texture.draw(ctx, center_at)
+        } else {
+            self.closed_texture.draw(ctx, center_at)
+        }
+    }
+}
+
+impl YSorted for Gate {
+    fn draw_layer(&self) -> DrawLayer {
+        match self.kind {
+            GateKind::Door => DrawLayer::Sorted,
+            GateKind::Spikes { .. } => DrawLayer::Below,
+        }
+    }
+}
@@ -1,63 +1,244 @@
-use super::collide::Collide;
+use super::camera::Camera;
+use super::choreography::Emote;
+use super::collide::{Collide, CornerNudge};
+use super::draw_order::{draw_order, YSorted};
+use super::encounter::{self, EncounterZone};
+use super::enemy::{self, Enemy};
+use super::exit::Exit;
+use super::follower::Follower;
 use super::geometry::TranslationContext;
+use super::hitbox::{Hitbox, Solid};
+use super::interaction::{self, probe_rect, Interact, InteractionZone};
+use super::layer::{self, Layer, ZOrder};
 use super::move_trait::{Direction, Move, MoveContext, Position};
 use super::multiwalk::MultiWalk;
-use super::pass_map::{BitmapPassMap, PassMap};
-use super::passability_checker::{PassMapPassabilityChecker, PassabilityCheck};
+use super::navigation::Agent;
+use super::npc::{self, Npc};
+use super::pass_map::{AnyPassMap, PassMap, TerrainClass};
+use super::passability_checker::{
+    EntityPassabilityChecker, PassMapPassabilityChecker, PassabilityCheck,
+};
+use super::pickup::{Pickup, PickupCreationParams};
 use super::player::Player;
-use super::walk::Walk;
+use super::puzzle::{self, Block, Gate, Switch};
+use super::room_state::{NpcState, RoomState};
+use super::sign::{Sign, SignCreationParams};
+use super::trigger::{self, Action, Trigger};
+use super::walk::{Walk, WalkState};
+use crate::audio::Sound;
+use crate::battle::BattleOutcome;
+use crate::dialog::Dialog;
+use crate::flags::Flags;
 use crate::game_context::GameContextRef;
-use crate::graphics::texture::Texture;
-use crate::graphics::Draw;
-use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use crate::geometry::{OverworldPoint, OverworldRect, ScreenVector};
+use crate::graphics::{screen_bounds, Draw};
+use crate::resources::{GlobalResourceStorage, ResourceStorage, ResourceStorageCloneExt};
 use crate::script::rust_script::RustScript;
-use crate::script::Script;
-use crate::ui_event::UiEvent;
+use crate::script::{Script, ScriptHandle};
+use crate::ui_event::{is_confirm_key, is_run_key, UiEvent};
 use ggez::input::keyboard::KeyCode;
 use ggez::{Context, GameResult};
+use oorandom::Rand32;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How far the player walks between two footstep sounds.
+const FOOTSTEP_STRIDE: f32 = 24.0;
+
+/// The gap between an emote and the top of the hitbox of the entity showing it.
+const EMOTE_MARGIN: f32 = 4.0;
+
+/// How long spikes leave the player alone after hurting them.
+const HURT_COOLDOWN: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct NoEntryPoint {
+    pub room_name: String,
+    pub prev_room_name: String,
+}
+
+impl Display for NoEntryPoint {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Room `{}` has no entry point for the player coming from `{}`",
+            self.room_name, self.prev_room_name
+        )
+    }
+}
+
+impl Error for NoEntryPoint {}
 
 #[derive(Debug, Clone)]
 pub struct PartialCreationParams {
-    pub background_path: String,
+    pub name: String,
+    pub layers: Vec<layer::CreationParams>,
     pub pass_map_path: String,
     pub initial_player_states: HashMap<String, (Position, Direction)>,
     pub init_script: Option<&'static str>,
+    pub npcs: Vec<npc::CreationParams>,
+    pub enemies: Vec<enemy::CreationParams>,
+    pub interaction_zones: Vec<interaction::ZoneCreationParams>,
+    pub triggers: Vec<trigger::CreationParams>,
+    pub encounter_zones: Vec<encounter::CreationParams>,
+    pub blocks: Vec<puzzle::BlockCreationParams>,
+    pub switches: Vec<puzzle::SwitchCreationParams>,
+    pub gates: Vec<puzzle::GateCreationParams>,
+    pub pickups: Vec<PickupCreationParams>,
+    pub signs: Vec<SignCreationParams>,
+    pub exits: Vec<Exit>,
+    pub corner_nudge: CornerNudge,
 }
 
 #[derive(Debug, Clone)]
 pub struct CreationParams {
-    pub background_path: String,
+    pub name: String,
+    pub layers: Vec<layer::CreationParams>,
     pub pass_map_path: String,
     pub player_position: Position,
     pub player_direction: Direction,
     pub init_script: Option<&'static str>,
+    pub npcs: Vec<npc::CreationParams>,
+    pub enemies: Vec<enemy::CreationParams>,
+    pub interaction_zones: Vec<interaction::ZoneCreationParams>,
+    pub triggers: Vec<trigger::CreationParams>,
+    pub encounter_zones: Vec<encounter::CreationParams>,
+    pub blocks: Vec<puzzle::BlockCreationParams>,
+    pub switches: Vec<puzzle::SwitchCreationParams>,
+    pub gates: Vec<puzzle::GateCreationParams>,
+    pub pickups: Vec<PickupCreationParams>,
+    pub signs: Vec<SignCreationParams>,
+    pub exits: Vec<Exit>,
+    pub corner_nudge: CornerNudge,
+    /// Seeds the random encounters. Taken from the clock unless set, e.g. to replay them.
+    pub seed: u64,
 }
 
 impl CreationParams {
-    pub fn from_partial(partial: PartialCreationParams, prev_room_name: &str) -> Self {
-        let (position, direction) = partial.initial_player_states[prev_room_name];
+    pub fn from_partial(
+        partial: PartialCreationParams,
+        prev_room_name: &str,
+    ) -> Result<Self, NoEntryPoint> {
+        let (position, direction) = match partial.initial_player_states.get(prev_room_name) {
+            Some(&state) => state,
+            None => {
+                return Err(NoEntryPoint {
+                    room_name: partial.name,
+                    prev_room_name: String::from(prev_room_name),
+                })
+            }
+        };
+        Ok(Self::with_player_at(partial, position, direction))
+    }
+
+    /// Puts the player at the given position instead of an entry point, e.g. when loading a game.
+    pub fn with_player_at(
+        partial: PartialCreationParams,
+        position: Position,
+        direction: Direction,
+    ) -> Self {
         CreationParams {
-            background_path: partial.background_path,
+            name: partial.name,
+            layers: partial.layers,
             pass_map_path: partial.pass_map_path,
             player_position: position,
             player_direction: direction,
             init_script: partial.init_script,
+            npcs: partial.npcs,
+            enemies: partial.enemies,
+            interaction_zones: partial.interaction_zones,
+            triggers: partial.triggers,
+            encounter_zones: partial.encounter_zones,
+            blocks: partial.blocks,
+            switches: partial.switches,
+            gates: partial.gates,
+            pickups: partial.pickups,
+            signs: partial.signs,
+            exits: partial.exits,
+            corner_nudge: partial.corner_nudge,
+            seed: random_seed(),
         }
     }
 }
 
 pub struct Room {
-    background: Texture,
-    pass_map: BitmapPassMap,
+    name: String,
+    layers: Vec<Layer>,
+    pass_map: AnyPassMap,
     player: Player,
+    camera: Camera,
+    npcs: Vec<Npc>,
+    enemies: Vec<Enemy>,
+    followers: Vec<Follower>,
+    interaction_zones: Vec<InteractionZone>,
+    triggers: Vec<Trigger>,
+    encounter_zones: Vec<EncounterZone>,
+    // Decides when random encounters happen and with whom.
+    rng: Rand32,
+    blocks: Vec<Block>,
+    switches: Vec<Switch>,
+    gates: Vec<Gate>,
+    pickups: Vec<Pickup>,
+    signs: Vec<Sign>,
     init_script: Option<Box<dyn Script>>,
+    active_interaction: Option<ScriptHandle>,
+    exits: Vec<Exit>,
+    // Which exits the player currently stands in; an exit is only taken when it is entered.
+    exits_occupied: Vec<bool>,
+    taken_exit: Option<String>,
+    corner_nudge: CornerNudge,
+    player_terrain: TerrainClass,
+    // Distance walked since the last footstep.
+    footstep_distance: f32,
+    // Named events waiting to be raised once the room is no longer locked.
+    raised_events: Vec<&'static str>,
+    emotes: Vec<Emote>,
+    // An encounter the player has run into, waiting for its battle to start.
+    encounter: Option<EncounterRequest>,
+    // How long until spikes can hurt the player again.
+    hurt_cooldown: Duration,
+}
+
+/// What started a battle, so that its outcome can be applied once it is over.
+#[derive(Debug, Clone)]
+pub enum EncounterSource {
+    /// The player has touched the enemy with this name.
+    Enemy(String),
+    /// The player has walked in an encounter zone long enough.
+    Zone { kill_counter: Option<&'static str> },
+}
+
+#[derive(Debug, Clone)]
+pub struct EncounterRequest {
+    pub source: EncounterSource,
+    pub encounter: &'static str,
+}
+
+/// Refers to an entity of the current room, e.g. from a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityId {
+    Player,
+    Npc(String),
+    // None of the shipped scripts directs an enemy yet.
+    #[allow(dead_code)]
+    Enemy(String),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum InteractionTarget {
+    Npc(usize),
+    Zone(usize),
+    Lever(usize),
+    Pickup(usize),
+    Sign(usize),
 }
 
 impl Room {
     pub fn new(params: CreationParams, global_resource_storage: &GlobalResourceStorage) -> Room {
         let player = Player::new(
-            &global_resource_storage,
+            global_resource_storage,
             MoveContext {
                 position: params.player_position,
                 direction: params.player_direction,
@@ -69,43 +250,598 @@ impl Room {
             Box::new(script) as Box<dyn Script>
         });
 
+        let npcs = params
+            .npcs
+            .iter()
+            .map(|npc_params| Npc::new(npc_params, global_resource_storage))
+            .collect();
+
+        let enemies = params
+            .enemies
+            .iter()
+            .map(|enemy_params| Enemy::new(enemy_params, global_resource_storage))
+            .collect();
+
+        let layers = params
+            .layers
+            .iter()
+            .map(|layer_params| Layer::new(layer_params, global_resource_storage))
+            .collect();
+
+        let interaction_zones = params
+            .interaction_zones
+            .iter()
+            .map(|zone_params| InteractionZone::new(zone_params, global_resource_storage))
+            .collect();
+
+        let blocks = params
+            .blocks
+            .iter()
+            .map(|block_params| Block::new(block_params, global_resource_storage))
+            .collect();
+        let switches = params
+            .switches
+            .iter()
+            .map(|switch_params| Switch::new(switch_params, global_resource_storage))
+            .collect();
+        let gates = params
+            .gates
+            .iter()
+            .map(|gate_params| Gate::new(gate_params, global_resource_storage))
+            .collect();
+        let pickups = params
+            .pickups
+            .iter()
+            .map(|pickup_params| Pickup::new(pickup_params, global_resource_storage))
+            .collect();
+        let signs = params
+            .signs
+            .iter()
+            .map(|sign_params| Sign::new(sign_params, global_resource_storage))
+            .collect();
+
+        let player_hitbox = player.hitbox_at(player.position());
+        let triggers = params
+            .triggers
+            .iter()
+            .map(|trigger_params| {
+                Trigger::new(trigger_params, &player_hitbox, global_resource_storage)
+            })
+            .collect();
+        let mut rng = Rand32::new(params.seed);
+        let encounter_zones = params
+            .encounter_zones
+            .iter()
+            .map(|zone_params| EncounterZone::new(zone_params, &mut rng))
+            .collect();
+        let exits_occupied = params
+            .exits
+            .iter()
+            .map(|exit| exit.rect.intersects(&player_hitbox))
+            .collect();
+
+        let camera = Camera::new(player.position());
+        let pass_map: AnyPassMap = global_resource_storage.get_cloned(&params.pass_map_path);
+        let player_terrain = pass_map.terrain_under(&player_hitbox).clone();
+
         Room {
-            background: global_resource_storage.get_cloned(&params.background_path),
-            pass_map: global_resource_storage.get_cloned(&params.pass_map_path),
+            name: params.name,
+            layers,
+            pass_map,
             init_script,
             player,
+            camera,
+            npcs,
+            enemies,
+            followers: Vec::new(),
+            interaction_zones,
+            triggers,
+            encounter_zones,
+            rng,
+            blocks,
+            switches,
+            gates,
+            pickups,
+            signs,
+            active_interaction: None,
+            exits: params.exits,
+            exits_occupied,
+            taken_exit: None,
+            corner_nudge: params.corner_nudge,
+            player_terrain,
+            footstep_distance: 0.0,
+            raised_events: Vec::new(),
+            emotes: Vec::new(),
+            encounter: None,
+            hurt_cooldown: Duration::ZERO,
         }
     }
 
     pub fn draw(&mut self, ggez: &mut Context, _ctx: GameContextRef<'_>) -> GameResult {
-        self.background.draw(
-            ggez,
-            self.background.dimensions().to_vector().to_point() * 0.5,
-        )?;
-        draw_entity(ggez, &self.translation_context(), &mut self.player)?;
+        let translation_context = self.translation_context();
+        for layer in self.layers_in(ZOrder::BelowEntities) {
+            layer.draw(ggez, &translation_context)?;
+        }
+
+        let mut entities: Vec<&dyn YSorted> = std::iter::once(&self.player as &dyn YSorted)
+            .chain(self.npcs.iter().map(|npc| npc as &dyn YSorted))
+            .chain(
+                self.enemies
+                    .iter()
+                    .filter(|enemy| enemy.is_active())
+                    .map(|enemy| enemy as &dyn YSorted),
+            )
+            .chain(
+                self.followers
+                    .iter()
+                    .map(|follower| follower as &dyn YSorted),
+            )
+            .chain(self.blocks.iter().map(|block| block as &dyn YSorted))
+            .chain(self.switches.iter().map(|switch| switch as &dyn YSorted))
+            .chain(self.gates.iter().map(|gate| gate as &dyn YSorted))
+            .chain(
+                self.pickups
+                    .iter()
+                    .filter(|pickup| !pickup.is_collected())
+                    .map(|pickup| pickup as &dyn YSorted),
+            )
+            .chain(
+                self.signs
+                    .iter()
+                    .filter(|sign| sign.is_visible())
+                    .map(|sign| sign as &dyn YSorted),
+            )
+            .collect();
+        entities.sort_by(|a, b| draw_order(*a, *b));
+        for entity in entities {
+            draw_entity(ggez, &translation_context, entity)?;
+        }
+        for emote in &self.emotes {
+            self.draw_emote(ggez, &translation_context, emote)?;
+        }
+
+        for layer in self.layers_in(ZOrder::AboveEntities) {
+            layer.draw(ggez, &translation_context)?;
+        }
         Ok(())
     }
 
-    pub fn update(&mut self, ggez: &mut Context, _ctx: GameContextRef<'_>) -> GameResult {
+    fn draw_emote(
+        &self,
+        ggez: &mut Context,
+        translation_context: &TranslationContext,
+        emote: &Emote,
+    ) -> GameResult {
+        let hitbox = match self.entity(&emote.entity) {
+            Some(agent) => agent.hitbox_at(agent.position()),
+            None => return Ok(()),
+        };
+        let head = OverworldPoint::new(hitbox.center().x, hitbox.min.y);
+        let offset = emote.texture.dimensions().height / 2.0 + EMOTE_MARGIN;
+        let center = translation_context.to_screen(head) - ScreenVector::new(0.0, offset);
+        emote.texture.draw(ggez, center)
+    }
+
+    fn layers_in(&self, z_order: ZOrder) -> impl Iterator<Item = &Layer> {
+        self.layers
+            .iter()
+            .filter(move |layer| layer.z_order() == z_order)
+    }
+
+    pub fn update(&mut self, ggez: &mut Context, ctx: GameContextRef<'_>) -> GameResult {
+        if let Some(script) = self.active_interaction {
+            if !ctx.runtime.lock().unwrap().borrow().is_running(script) {
+                self.active_interaction = None;
+            }
+        }
+
         let time_slice = ggez::timer::delta(ggez);
+        self.update_gates(ctx);
+        self.update_pickups(ctx);
+        let player_hitbox = self.player.hitbox_at(self.player.position());
+        for enemy in self.enemies.iter_mut().filter(|enemy| enemy.is_active()) {
+            enemy.think(&player_hitbox, &self.pass_map, time_slice);
+        }
+        let pass_map_checker = PassMapPassabilityChecker::new(&self.pass_map);
+        let mut solid_hitboxes = self.solid_hitboxes();
 
-        let assumed_new_player_position = self.player.get_updated_position(time_slice);
-        let maybe_new_player_position = self.player.find_passable_position(
-            self.player.position(),
-            assumed_new_player_position,
-            &self.passability_checker(),
+        let old_player_position = self.player.position();
+        apply_terrain(&self.pass_map, &mut self.player);
+        solid_hitboxes[0] = move_entity(
+            &mut self.player,
+            time_slice,
+            self.corner_nudge,
+            &passability_checker_for(&pass_map_checker, &solid_hitboxes, 0),
         );
-        if let Some(new_player_position) = maybe_new_player_position {
-            self.player.set_position(new_player_position)
+        for (index, npc) in self.npcs.iter_mut().enumerate() {
+            let slot = index + 1;
+            apply_terrain(&self.pass_map, npc);
+            solid_hitboxes[slot] = move_entity(
+                npc,
+                time_slice,
+                self.corner_nudge,
+                &passability_checker_for(&pass_map_checker, &solid_hitboxes, slot),
+            );
+        }
+        for (index, enemy) in self.enemies.iter_mut().enumerate() {
+            let slot = index + 1 + self.npcs.len();
+            if !enemy.is_active() {
+                continue;
+            }
+            apply_terrain(&self.pass_map, enemy);
+            solid_hitboxes[slot] = move_entity(
+                enemy,
+                time_slice,
+                self.corner_nudge,
+                &passability_checker_for(&pass_map_checker, &solid_hitboxes, slot),
+            );
+        }
+        for follower in &mut self.followers {
+            follower.follow(self.player.as_ref());
         }
+        self.update_blocks(time_slice);
+        self.update_spikes(ctx, time_slice);
+        self.update_switches(ctx);
+        self.update_enemy_contacts();
 
+        self.update_player_terrain();
+        let walked = (self.player.position() - old_player_position).length();
+        self.update_encounter_zones(ctx, walked);
+        self.update_footsteps(ggez, ctx, walked)?;
+        self.update_triggers(ctx, time_slice);
+        self.update_exits();
+        self.emotes.retain(|emote| !emote.is_finished());
+        self.camera.update(
+            self.player.position(),
+            self.pass_map.bounds(),
+            screen_bounds(ggez).size().cast_unit(),
+            time_slice,
+        );
         Ok(())
     }
 
-    pub fn handle_event(&mut self, _ggez: &mut Context, _ctx: GameContextRef<'_>, event: UiEvent) {
+    // Each entity which walks has a slot here, so that it can be excluded from its own checks:
+    // the player, then NPCs, then enemies. Obstacles which don't walk by themselves come after
+    // them, starting with blocks.
+    fn solid_hitboxes(&self) -> Vec<Option<OverworldRect<f32>>> {
+        std::iter::once(self.player.solid_hitbox())
+            .chain(self.npcs.iter().map(Solid::solid_hitbox))
+            .chain(self.enemies.iter().map(Solid::solid_hitbox))
+            .chain(self.blocks.iter().map(Solid::solid_hitbox))
+            .chain(self.switches.iter().map(Solid::solid_hitbox))
+            .chain(self.gates.iter().map(Solid::solid_hitbox))
+            .chain(self.signs.iter().map(Solid::solid_hitbox))
+            .collect()
+    }
+
+    fn update_pickups(&mut self, ctx: GameContextRef<'_>) {
+        let flags = ctx.flags.lock().unwrap();
+        let flags = flags.borrow();
+        for pickup in &mut self.pickups {
+            pickup.update(&flags);
+        }
+    }
+
+    fn update_gates(&mut self, ctx: GameContextRef<'_>) {
+        let occupants: Vec<_> = std::iter::once(self.player.solid_hitbox())
+            .chain(self.npcs.iter().map(Solid::solid_hitbox))
+            .chain(self.enemies.iter().map(Solid::solid_hitbox))
+            .chain(self.blocks.iter().map(Solid::solid_hitbox))
+            .flatten()
+            .collect();
+        let flags = ctx.flags.lock().unwrap();
+        let flags = flags.borrow();
+        for gate in &mut self.gates {
+            let hitbox = gate.hitbox_at(gate.position());
+            let occupied = occupants.iter().any(|other| other.intersects(&hitbox));
+            gate.update(&flags, occupied);
+        }
+    }
+
+    // What is right in front of the player while they walk, and which way they are walking.
+    fn player_walks_into(&self) -> Option<(Direction, OverworldRect<f32>)> {
+        match self.player.walk_state() {
+            WalkState::Walking(_) => {
+                let direction = self.player.direction();
+                let hitbox = self.player.hitbox_at(self.player.position());
+                Some((direction, hitbox.translate(direction.to_vector())))
+            }
+            WalkState::Still => None,
+        }
+    }
+
+    fn update_blocks(&mut self, time_slice: Duration) {
+        // The player pushes whatever is right in front of them while walking.
+        let push = self.player_walks_into();
+        let pass_map_checker = PassMapPassabilityChecker::new(&self.pass_map);
+        let first_slot = 1 + self.npcs.len() + self.enemies.len();
+        for index in 0..self.blocks.len() {
+            let solid_hitboxes = self.solid_hitboxes();
+            let block = &mut self.blocks[index];
+            let block_hitbox = block.hitbox_at(block.position());
+            let pushed = push
+                .filter(|(_, probe)| probe.intersects(&block_hitbox))
+                .map(|(direction, _)| direction);
+            block.update(
+                pushed,
+                time_slice,
+                &passability_checker_for(&pass_map_checker, &solid_hitboxes, first_slot + index),
+            );
+        }
+    }
+
+    fn update_spikes(&mut self, ctx: GameContextRef<'_>, time_slice: Duration) {
+        self.hurt_cooldown = self.hurt_cooldown.saturating_sub(time_slice);
+        if !self.hurt_cooldown.is_zero() {
+            return;
+        }
+        let probe = match self.player_walks_into() {
+            Some((_, probe)) => probe,
+            None => return,
+        };
+        let damage = self.gates.iter().find_map(|gate| {
+            gate.damage()
+                .filter(|_| gate.hitbox_at(gate.position()).intersects(&probe))
+        });
+        if let Some(damage) = damage {
+            let flags = ctx.flags.lock().unwrap();
+            flags.borrow_mut().add(puzzle::DAMAGE_TAKEN_FLAG, damage);
+            self.hurt_cooldown = HURT_COOLDOWN;
+        }
+    }
+
+    fn update_enemy_contacts(&mut self) {
+        if self.encounter.is_some() {
+            return;
+        }
+        let player_hitbox = self.player.hitbox_at(self.player.position());
+        let enemy = match self
+            .enemies
+            .iter_mut()
+            .find(|enemy| enemy.touches(&player_hitbox))
+        {
+            Some(enemy) => enemy,
+            None => return,
+        };
+        enemy.engage();
+        self.encounter = Some(EncounterRequest {
+            source: EncounterSource::Enemy(String::from(enemy.name())),
+            encounter: enemy.encounter(),
+        });
+        self.stop_player();
+    }
+
+    fn update_encounter_zones(&mut self, ctx: GameContextRef<'_>, walked: f32) {
+        if self.encounter.is_some() {
+            return;
+        }
+        let player_hitbox = self.player.hitbox_at(self.player.position());
+        let flags = ctx.flags.lock().unwrap();
+        let flags = flags.borrow();
+        for zone in &mut self.encounter_zones {
+            if let Some(encounter) = zone.update(&player_hitbox, walked, &flags, &mut self.rng) {
+                self.encounter = Some(EncounterRequest {
+                    source: EncounterSource::Zone {
+                        kill_counter: encounter.kill_counter,
+                    },
+                    encounter: encounter.encounter,
+                });
+                break;
+            }
+        }
+        if self.encounter.is_some() {
+            self.stop_player();
+        }
+    }
+
+    /// Returns the encounter the player has run into, if any.
+    pub fn take_encounter(&mut self) -> Option<EncounterRequest> {
+        self.encounter.take()
+    }
+
+    /// Applies the outcome of the battle to whatever started it.
+    pub fn resolve_encounter(
+        &mut self,
+        source: &EncounterSource,
+        outcome: BattleOutcome,
+        flags: &mut Flags,
+    ) {
+        match source {
+            EncounterSource::Enemy(name) => {
+                if let Some(enemy) = self.enemy_mut(name) {
+                    enemy.resolve(outcome);
+                }
+            }
+            EncounterSource::Zone {
+                kill_counter: Some(counter),
+            } if outcome == BattleOutcome::Won => flags.add(counter, 1),
+            EncounterSource::Zone { .. } => (),
+        }
+    }
+
+    fn update_switches(&mut self, ctx: GameContextRef<'_>) {
+        let pressing: Vec<_> = std::iter::once(self.player.hitbox_at(self.player.position()))
+            .chain(
+                self.blocks
+                    .iter()
+                    .map(|block| block.hitbox_at(block.position())),
+            )
+            .collect();
+        let flags = ctx.flags.lock().unwrap();
+        let mut flags = flags.borrow_mut();
+        for switch in &mut self.switches {
+            switch.update(&pressing, &mut flags);
+        }
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    fn update_player_terrain(&mut self) {
+        let player_hitbox = self.player.hitbox_at(self.player.position());
+        let terrain = self.pass_map.terrain_under(&player_hitbox);
+        if *terrain == self.player_terrain {
+            return;
+        }
+        self.raised_events.extend(
+            self.player_terrain
+                .leave_event
+                .iter()
+                .chain(&terrain.enter_event),
+        );
+        self.player_terrain = terrain.clone();
+    }
+
+    fn update_footsteps(
+        &mut self,
+        ggez: &mut Context,
+        ctx: GameContextRef<'_>,
+        walked: f32,
+    ) -> GameResult {
+        if walked == 0.0 {
+            self.footstep_distance = 0.0;
+            return Ok(());
+        }
+        self.footstep_distance += walked;
+        if self.footstep_distance < FOOTSTEP_STRIDE {
+            return Ok(());
+        }
+        self.footstep_distance %= FOOTSTEP_STRIDE;
+        match self.player_terrain.footsteps {
+            Some(name) => {
+                let sound: &Sound = ctx.global_resource_storage.get(name);
+                sound.play(ggez)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn update_triggers(&mut self, ctx: GameContextRef<'_>, time_slice: Duration) {
+        let player_hitbox = self.player.hitbox_at(self.player.position());
+        let flags = ctx.flags.lock().unwrap();
+        let flags = flags.borrow();
+        for trigger in &mut self.triggers {
+            let kind = match trigger.update(&player_hitbox, time_slice, &flags) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            match trigger.action_mut(kind) {
+                Some(Action::Event(name)) => self.raised_events.push(name),
+                Some(Action::Script(script)) => {
+                    ctx.runtime
+                        .lock()
+                        .unwrap()
+                        .borrow_mut()
+                        .start_script(ctx.to_owned(), script.as_mut());
+                }
+                None => (),
+            }
+        }
+    }
+
+    fn update_exits(&mut self) {
+        let player_hitbox = self.player.hitbox_at(self.player.position());
+        for (exit, occupied) in self.exits.iter().zip(self.exits_occupied.iter_mut()) {
+            let now_occupied = exit.rect.intersects(&player_hitbox);
+            if now_occupied && !*occupied && self.taken_exit.is_none() {
+                self.taken_exit = Some(exit.target_room.clone());
+            }
+            *occupied = now_occupied;
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn player_position(&self) -> Position {
+        self.player.position()
+    }
+
+    pub fn player_direction(&self) -> Direction {
+        self.player.direction()
+    }
+
+    pub fn capture_state(&self) -> RoomState {
+        let used_triggers = self
+            .triggers
+            .iter()
+            .filter(|trigger| trigger.is_used())
+            .map(|trigger| String::from(trigger.name()))
+            .collect();
+        let npcs = self
+            .npcs
+            .iter()
+            .map(|npc| {
+                let state = NpcState {
+                    position: npc.position().to_array(),
+                    direction: npc.direction(),
+                };
+                (String::from(npc.name()), state)
+            })
+            .collect();
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let position = block.resting_position().to_array();
+                (String::from(block.name()), position)
+            })
+            .collect();
+        let enemies = self
+            .enemies
+            .iter()
+            .filter_map(|enemy| Some((String::from(enemy.name()), enemy.fate()?)))
+            .collect();
+        RoomState {
+            used_triggers,
+            npcs,
+            blocks,
+            enemies,
+        }
+    }
+
+    /// Brings back the state captured when the player was here before. Entities which are no
+    /// longer in the manifest are ignored.
+    pub fn restore_state(&mut self, state: &RoomState) {
+        for trigger in &mut self.triggers {
+            if state.used_triggers.contains(trigger.name()) {
+                trigger.mark_used();
+            }
+        }
+        for npc in &mut self.npcs {
+            if let Some(npc_state) = state.npcs.get(npc.name()) {
+                npc.set_position(npc_state.position.into());
+                npc.set_direction(npc_state.direction);
+            }
+        }
+        for block in &mut self.blocks {
+            if let Some(&position) = state.blocks.get(block.name()) {
+                block.place_at(position.into());
+            }
+        }
+        for enemy in &mut self.enemies {
+            if let Some(&fate) = state.enemies.get(enemy.name()) {
+                enemy.set_fate(fate);
+            }
+        }
+    }
+
+    /// Returns the names of the events raised by the room since the last call.
+    pub fn take_raised_events(&mut self) -> Vec<&'static str> {
+        std::mem::take(&mut self.raised_events)
+    }
+
+    /// Returns the name of the room the player has walked into, if any.
+    pub fn take_exit(&mut self) -> Option<String> {
+        self.taken_exit.take()
+    }
+
+    pub fn handle_event(&mut self, _ggez: &mut Context, ctx: GameContextRef<'_>, event: UiEvent) {
         match event {
             UiEvent::KeyDown { key, .. } => {
-                self.handle_key_down(key);
+                self.handle_key_down(ctx, key);
             }
             UiEvent::KeyUp { key, .. } => {
                 self.handle_key_up(key);
@@ -119,26 +855,120 @@ impl Room {
             return;
         }
 
-        let [x, y]: [f32; 2] = multi_walk_state.resulting_velocity().into();
+        let direction = Direction::from_vector(multi_walk_state.direction());
+        self.player.set_direction(direction);
+    }
+
+    fn is_player_locked(&self) -> bool {
+        self.active_interaction.is_some()
+    }
+
+    fn find_interaction_target(&self) -> Option<InteractionTarget> {
+        let player_position = self.player.position();
+        let probe = probe_rect(
+            &self.player.hitbox_at(player_position),
+            self.player.direction(),
+        );
+        let distance_to = |rect: OverworldRect<f32>| (rect.center() - player_position).length();
+
+        let npc_candidates = self
+            .npcs
+            .iter()
+            .enumerate()
+            .filter(|(_, npc)| npc.can_interact())
+            .map(|(index, npc)| (npc.interaction_rect(), InteractionTarget::Npc(index)));
+        let zone_candidates = self
+            .interaction_zones
+            .iter()
+            .enumerate()
+            .map(|(index, zone)| (zone.rect(), InteractionTarget::Zone(index)));
+        let lever_candidates = self
+            .switches
+            .iter()
+            .enumerate()
+            .filter(|(_, switch)| switch.is_lever())
+            .map(|(index, lever)| {
+                let rect = lever.hitbox_at(lever.position());
+                (rect, InteractionTarget::Lever(index))
+            });
+        let pickup_candidates = self
+            .pickups
+            .iter()
+            .enumerate()
+            .filter(|(_, pickup)| !pickup.is_collected())
+            .map(|(index, pickup)| {
+                let rect = pickup.hitbox_at(pickup.position());
+                (rect, InteractionTarget::Pickup(index))
+            });
+        let sign_candidates = self
+            .signs
+            .iter()
+            .enumerate()
+            .map(|(index, sign)| (sign.interaction_rect(), InteractionTarget::Sign(index)));
 
-        let direction = if x.abs() >= y.abs() {
-            if x > 0.0 {
-                Direction::Right
-            } else {
-                Direction::Left
+        npc_candidates
+            .chain(zone_candidates)
+            .chain(lever_candidates)
+            .chain(pickup_candidates)
+            .chain(sign_candidates)
+            .filter(|(rect, _)| rect.intersects(&probe))
+            .map(|(rect, target)| (distance_to(rect), target))
+            .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+            .map(|(_, target)| target)
+    }
+
+    fn interact(&mut self, ctx: GameContextRef<'_>) {
+        // Declared here to outlive the match, as the dialog for a pickup is made on the spot.
+        let mut pickup_dialog;
+        let script: &mut dyn Script = match self.find_interaction_target() {
+            Some(InteractionTarget::Npc(index)) => {
+                self.npcs[index].interaction_script_mut().unwrap().as_mut()
+            }
+            Some(InteractionTarget::Zone(index)) => {
+                self.interaction_zones[index].script_mut().as_mut()
             }
-        } else {
-            if y > 0.0 {
-                Direction::Backward
-            } else {
-                Direction::Forward
+            Some(InteractionTarget::Sign(index)) => {
+                self.signs[index].interaction_script_mut().unwrap().as_mut()
             }
+            Some(InteractionTarget::Pickup(index)) => {
+                let inventory = ctx.inventory.lock().unwrap();
+                let flags = ctx.flags.lock().unwrap();
+                let frames = self.pickups[index]
+                    .pick_up(&mut inventory.borrow_mut(), &mut flags.borrow_mut());
+                if frames.is_empty() {
+                    return;
+                }
+                pickup_dialog = Dialog::new(frames);
+                &mut pickup_dialog
+            }
+            Some(InteractionTarget::Lever(index)) => {
+                let flags = ctx.flags.lock().unwrap();
+                self.switches[index].toggle(&mut flags.borrow_mut());
+                return;
+            }
+            None => return,
         };
 
-        self.player.set_direction(direction);
+        let handle = ctx
+            .runtime
+            .lock()
+            .unwrap()
+            .borrow_mut()
+            .start_script(ctx.to_owned(), script);
+        self.active_interaction = Some(handle);
+        self.player.stop_walking_in_all_directions();
     }
 
-    fn handle_key_down(&mut self, key: KeyCode) {
+    fn handle_key_down(&mut self, ctx: GameContextRef<'_>, key: KeyCode) {
+        // Holding the run key does not move the player by itself, so it is never locked.
+        if is_run_key(key) {
+            self.player.set_running(true);
+            return;
+        }
+        if self.is_player_locked() {
+            return;
+        }
+
         match key {
             KeyCode::Up => {
                 self.player.start_walking_forward();
@@ -156,6 +986,7 @@ impl Room {
                 self.player.start_walking_right();
                 self.update_player_direction();
             }
+            key if is_confirm_key(key) => self.interact(ctx),
             _ => (),
         }
     }
@@ -178,32 +1009,208 @@ impl Room {
                 self.player.stop_walking_right();
                 self.update_player_direction();
             }
+            key if is_run_key(key) => self.player.set_running(false),
             _ => (),
         }
     }
 
-    fn passability_checker<'a>(&'a self) -> impl PassabilityCheck + 'a {
-        PassMapPassabilityChecker::new(self.pass_map())
+    fn translation_context(&self) -> TranslationContext {
+        self.camera.translation_context()
+    }
+
+    pub fn init_script_mut(&mut self) -> Option<&mut Box<dyn Script>> {
+        self.init_script.as_mut()
     }
 
-    fn pass_map(&self) -> &impl PassMap {
+    pub fn npc_mut(&mut self, name: &str) -> Option<&mut Npc> {
+        self.npcs.iter_mut().find(|npc| npc.name() == name)
+    }
+
+    pub fn enemy_mut(&mut self, name: &str) -> Option<&mut Enemy> {
+        self.enemies.iter_mut().find(|enemy| enemy.name() == name)
+    }
+
+    pub fn entity(&self, entity: &EntityId) -> Option<&dyn Agent> {
+        match entity {
+            EntityId::Player => Some(&self.player),
+            EntityId::Npc(name) => self
+                .npcs
+                .iter()
+                .find(|npc| npc.name() == name)
+                .map(|npc| npc as &dyn Agent),
+            EntityId::Enemy(name) => self
+                .enemies
+                .iter()
+                .find(|enemy| enemy.name() == name && enemy.is_active())
+                .map(|enemy| enemy as &dyn Agent),
+        }
+    }
+
+    pub fn entity_mut(&mut self, entity: &EntityId) -> Option<&mut dyn Agent> {
+        match entity {
+            EntityId::Player => Some(&mut self.player),
+            EntityId::Npc(name) => self.npc_mut(name).map(|npc| npc as &mut dyn Agent),
+            EntityId::Enemy(name) => self
+                .enemy_mut(name)
+                .filter(|enemy| enemy.is_active())
+                .map(|enemy| enemy as &mut dyn Agent),
+        }
+    }
+
+    pub fn pass_map(&self) -> &AnyPassMap {
         &self.pass_map
     }
 
-    fn translation_context(&self) -> TranslationContext {
-        TranslationContext
+    /// Puts the follower on the player and makes it walk behind them.
+    pub fn add_follower(&mut self, mut follower: Follower) {
+        follower.reset(self.player.as_ref());
+        self.followers.push(follower);
     }
 
-    pub fn init_script_mut(&mut self) -> Option<&mut Box<dyn Script>> {
-        self.init_script.as_mut()
+    pub fn remove_follower(&mut self, name: &str) -> Option<Follower> {
+        let index = self
+            .followers
+            .iter()
+            .position(|follower| follower.name() == name)?;
+        Some(self.followers.remove(index))
+    }
+
+    pub fn followers(&self) -> &[Follower] {
+        &self.followers
+    }
+
+    pub fn take_followers(&mut self) -> Vec<Follower> {
+        std::mem::take(&mut self.followers)
+    }
+
+    pub fn show_emote(&mut self, emote: Emote) {
+        self.emotes.push(emote);
+    }
+
+    /// Makes the player stand still, regardless of the keys being held.
+    pub fn stop_player(&mut self) {
+        self.player.stop_walking_in_all_directions();
+        self.player.set_running(false);
+    }
+}
+
+fn passability_checker_for<P: PassabilityCheck>(
+    inner: P,
+    solid_hitboxes: &[Option<OverworldRect<f32>>],
+    slot: usize,
+) -> EntityPassabilityChecker<P> {
+    let own_hitbox = solid_hitboxes[slot];
+    // Entities which already overlap are allowed to walk apart instead of getting stuck.
+    let other_hitboxes = solid_hitboxes
+        .iter()
+        .enumerate()
+        .filter(|&(other_slot, _)| other_slot != slot)
+        .filter_map(|(_, &hitbox)| hitbox)
+        .filter(|hitbox| own_hitbox.is_none_or(|own| !own.intersects(hitbox)));
+    EntityPassabilityChecker::new(inner, other_hitboxes)
+}
+
+fn apply_terrain(pass_map: &AnyPassMap, entity: &mut (impl Walk + Hitbox)) {
+    let terrain = pass_map.terrain_under(&entity.hitbox_at(entity.position()));
+    entity
+        .movement_mut()
+        .set_terrain_factor(terrain.speed_factor);
+}
+
+// Returns the solid hitbox of the entity at its new position.
+fn move_entity(
+    entity: &mut (impl Walk + Collide + Solid),
+    time_slice: Duration,
+    corner_nudge: CornerNudge,
+    pass: &impl PassabilityCheck,
+) -> Option<OverworldRect<f32>> {
+    let old_position = entity.position();
+    let assumed_new_position = entity.get_updated_position(time_slice);
+    let maybe_new_position =
+        entity.find_passable_position(old_position, assumed_new_position, corner_nudge, pass);
+    if let Some(new_position) = maybe_new_position {
+        entity.set_position(new_position)
     }
+    let new_position = maybe_new_position.unwrap_or(old_position);
+    entity
+        .movement_mut()
+        .stop_against(assumed_new_position - new_position);
+    entity.solid_hitbox()
 }
 
 fn draw_entity(
     ctx: &mut Context,
     translation_context: &TranslationContext,
-    entity: &mut (impl Draw + Move),
+    entity: &(impl Draw + Move + ?Sized),
 ) -> GameResult {
     let screen_position = translation_context.to_screen(entity.position());
     entity.draw(ctx, screen_position)
 }
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PartialCreationParams;
+    use crate::overworld::move_trait::{Direction, Position};
+    use crate::overworld::puzzle::{GateKind, SwitchKind};
+    use crate::overworld::trigger::Shape;
+    use underkate_tools::load_room;
+
+    #[test]
+    fn every_manifest_section_is_loaded() {
+        let room: PartialCreationParams = load_room!("test/everything");
+        assert_eq!(room.name, "test/everything");
+        assert_eq!(room.pass_map_path, "test/terrain");
+        assert_eq!(room.corner_nudge.max_distance, 6.0);
+        assert_eq!(room.layers.len(), 2);
+        assert_eq!(room.layers[1].parallax, 1.5);
+        assert_eq!(room.initial_player_states.len(), 1);
+        assert_eq!(
+            room.initial_player_states["_"],
+            (Position::new(24.0, 16.0), Direction::Forward)
+        );
+        assert_eq!(room.init_script, Some("test/everything/init"));
+        assert_eq!(room.npcs.len(), 1);
+        assert_eq!(room.npcs[0].movement.run_speed, 120.0);
+        assert_eq!(
+            room.npcs[0].interaction_script,
+            Some("test/everything/talk")
+        );
+        assert_eq!(room.enemies.len(), 1);
+        assert_eq!(room.enemies[0].patrol.len(), 2);
+        assert_eq!(room.enemies[0].encounter, "test");
+        assert_eq!(room.interaction_zones.len(), 1);
+        assert_eq!(room.interaction_zones[0].script, "test/everything/look");
+        assert_eq!(room.triggers.len(), 2);
+        assert_eq!(room.triggers[0].name, "entrance");
+        assert_eq!(room.triggers[0].conditions.len(), 2);
+        assert!(room.triggers[0].on_stay.is_some());
+        assert!(matches!(room.triggers[1].shape, Shape::Polygon(_)));
+        assert_eq!(room.encounter_zones.len(), 1);
+        assert_eq!(room.encounter_zones[0].table.groups.len(), 2);
+        assert_eq!(room.encounter_zones[0].table.groups[1].weight, 3);
+        assert_eq!(room.blocks.len(), 1);
+        assert_eq!(room.blocks[0].name, "crate");
+        assert_eq!(room.blocks[0].grid, 4.0);
+        assert_eq!(room.switches.len(), 2);
+        assert_eq!(room.switches[0].kind, SwitchKind::Floor { sticky: true });
+        assert_eq!(room.switches[1].flag, "test.lever");
+        assert_eq!(room.gates.len(), 2);
+        assert_eq!(room.gates[0].kind, GateKind::Door);
+        assert_eq!(room.gates[0].open_when.len(), 2);
+        assert_eq!(room.gates[1].kind, GateKind::Spikes { damage: 2 });
+        assert_eq!(room.pickups.len(), 1);
+        assert_eq!(room.pickups[0].item, "key");
+        assert_eq!(room.pickups[0].flag, "test.key_taken");
+        assert_eq!(room.signs.len(), 2);
+        assert_eq!(room.signs[0].text.len(), 2);
+        assert!(room.signs[1].texture.is_none());
+        assert_eq!(room.exits.len(), 1);
+        assert_eq!(room.exits[0].target_room, "home/room");
+    }
+}
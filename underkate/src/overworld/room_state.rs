@@ -0,0 +1,48 @@
+use super::enemy::EnemyFate;
+use super::move_trait::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpcState {
+    pub position: [f32; 2],
+    pub direction: Direction,
+}
+
+/// The parts of a room which can change while the player is in it, so that the room looks the
+/// same when they come back.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoomState {
+    /// Names of the once-only triggers which have already fired.
+    #[serde(default)]
+    pub used_triggers: BTreeSet<String>,
+    #[serde(default)]
+    pub npcs: BTreeMap<String, NpcState>,
+    /// Positions of the pushable blocks, keyed by name.
+    #[serde(default)]
+    pub blocks: BTreeMap<String, [f32; 2]>,
+    /// The enemies the player has dealt with, keyed by name.
+    #[serde(default)]
+    pub enemies: BTreeMap<String, EnemyFate>,
+}
+
+/// The states of the rooms the player has left, keyed by room name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RoomStateStore {
+    rooms: BTreeMap<String, RoomState>,
+}
+
+impl RoomStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, room_name: &str) -> Option<&RoomState> {
+        self.rooms.get(room_name)
+    }
+
+    pub fn store(&mut self, room_name: &str, state: RoomState) {
+        self.rooms.insert(String::from(room_name), state);
+    }
+}
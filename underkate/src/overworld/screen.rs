@@ -1,24 +1,166 @@
-use super::room::Room;
+use super::room::{CreationParams, EncounterSource, PartialCreationParams, Room};
+use super::room_state::RoomStateStore;
+use crate::battle::{BattleOutcome, BattleScreen};
+use crate::dialog::dialog_box::DialogBox;
+use crate::dialog::history::DialogHistoryViewer;
 use crate::game_context::GameContextRef;
+use crate::resources::ResourceStorageCloneExt;
 use crate::screen::Screen;
-use crate::ui_event::UiEvent;
+use crate::ui_event::{UiEvent, DIALOG_HISTORY_KEY};
 use ggez::graphics::{self, Color};
-use ggez::GameResult;
+use ggez::{GameError, GameResult};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A battle the overworld asks the game to switch to.
+pub struct BattleRequest {
+    pub screen: BattleScreen,
+    /// What started the battle, unless it was a script.
+    pub source: Option<EncounterSource>,
+}
 
 pub struct OverworldScreen {
     room: Option<Room>,
+    battle_request: Option<BattleRequest>,
+    // Set from the moment a battle is requested until it is over.
+    in_battle: bool,
+    dialog_box: Option<DialogBox>,
+    history_viewer: Option<DialogHistoryViewer>,
+    raised_events: Vec<&'static str>,
+    room_states: RoomStateStore,
+    save_requested: bool,
+    // The number of cutscenes in progress. The player can't move while there are any.
+    cutscenes: usize,
 }
 
 impl OverworldScreen {
     pub fn new() -> OverworldScreen {
-        OverworldScreen { room: None }
+        OverworldScreen {
+            room: None,
+            battle_request: None,
+            in_battle: false,
+            dialog_box: None,
+            history_viewer: None,
+            raised_events: Vec::new(),
+            room_states: RoomStateStore::new(),
+            save_requested: false,
+            cutscenes: 0,
+        }
     }
 
-    pub fn load_room(&mut self, ctx: GameContextRef<'_>, room: Room) {
+    pub fn load_room(&mut self, ctx: GameContextRef<'_>, mut room: Room) {
+        if let Some(state) = self.room_states.get(room.name()) {
+            room.restore_state(state);
+        }
         self.room = Some(room);
         self.init_room(ctx);
     }
 
+    fn enter_room(&mut self, ctx: GameContextRef<'_>, room_name: &str) -> GameResult {
+        let prev_room = self.room.as_ref().unwrap();
+        let prev_room_name = prev_room.name();
+        let partial: PartialCreationParams = ctx
+            .global_resource_storage
+            .try_get_cloned(room_name)
+            .map_err(|e| GameError::CustomError(e.to_string()))?;
+        let params = CreationParams::from_partial(partial, prev_room_name)
+            .map_err(|e| GameError::CustomError(e.to_string()))?;
+        self.room_states
+            .store(prev_room_name, prev_room.capture_state());
+        let mut room = Room::new(params, ctx.global_resource_storage);
+        for follower in self.room_mut().take_followers() {
+            room.add_follower(follower);
+        }
+        self.load_room(ctx, room);
+        Ok(())
+    }
+
+    pub fn room(&self) -> &Room {
+        self.room.as_ref().unwrap()
+    }
+
+    pub fn room_mut(&mut self) -> &mut Room {
+        self.room.as_mut().unwrap()
+    }
+
+    /// Returns the names of the events raised in the overworld since the last call. They are kept
+    /// here rather than raised right away, because scripts woken up by them lock the screen.
+    pub fn take_raised_events(&mut self) -> Vec<&'static str> {
+        std::mem::take(&mut self.raised_events)
+    }
+
+    /// The states of all the visited rooms, including the current one.
+    pub fn room_states(&self) -> RoomStateStore {
+        let mut room_states = self.room_states.clone();
+        let room = self.room();
+        room_states.store(room.name(), room.capture_state());
+        room_states
+    }
+
+    /// Replaces the states of the visited rooms. Takes effect when a room is loaded next time.
+    pub fn set_room_states(&mut self, room_states: RoomStateStore) {
+        self.room_states = room_states;
+    }
+
+    /// Asks for the game to be saved at the end of the frame, once scripts no longer hold the
+    /// screen.
+    pub fn request_save(&mut self) {
+        self.save_requested = true;
+    }
+
+    pub fn take_save_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_requested)
+    }
+
+    pub fn begin_cutscene(&mut self) {
+        self.cutscenes += 1;
+        self.room_mut().stop_player();
+    }
+
+    pub fn end_cutscene(&mut self) {
+        self.cutscenes = self
+            .cutscenes
+            .checked_sub(1)
+            .expect("No cutscene is in progress");
+        if self.cutscenes == 0 {
+            self.room_mut().stop_player();
+        }
+    }
+
+    /// Asks the game to leave the overworld for the battle. The room stays paused until
+    /// `finish_battle` is called.
+    pub fn start_battle(&mut self, screen: BattleScreen, source: Option<EncounterSource>) {
+        self.room_mut().stop_player();
+        self.battle_request = Some(BattleRequest { screen, source });
+        self.in_battle = true;
+    }
+
+    pub fn take_battle_request(&mut self) -> Option<BattleRequest> {
+        self.battle_request.take()
+    }
+
+    /// Comes back from a battle, letting the room know how the encounter it started went.
+    pub fn finish_battle(
+        &mut self,
+        ctx: GameContextRef<'_>,
+        source: Option<EncounterSource>,
+        outcome: BattleOutcome,
+    ) {
+        self.in_battle = false;
+        if let Some(source) = source {
+            let flags = ctx.flags.lock().unwrap();
+            let mut flags = flags.borrow_mut();
+            self.room_mut()
+                .resolve_encounter(&source, outcome, &mut flags);
+        }
+    }
+
+    pub fn show_dialog(&mut self, dialog_box: DialogBox) {
+        if !dialog_box.is_finished() {
+            self.dialog_box = Some(dialog_box);
+        }
+    }
+
     fn init_room(&mut self, ctx: GameContextRef<'_>) {
         if let Some(script) = self.room.as_mut().unwrap().init_script_mut() {
             ctx.runtime
@@ -28,19 +170,79 @@ impl OverworldScreen {
                 .start_script(ctx.to_owned(), script.as_mut());
         }
     }
+
+    pub fn is_room_paused(&self) -> bool {
+        self.in_battle || self.dialog_box.is_some() || self.history_viewer.is_some()
+    }
+
+    // Returns `true` if the event has been consumed by an overlay.
+    fn handle_overlay_event(&mut self, ctx: GameContextRef<'_>, event: UiEvent) -> bool {
+        let key = match event {
+            UiEvent::KeyDown { key, .. } => key,
+            // Key releases always reach the room, so that the player does not keep walking.
+            UiEvent::KeyUp { .. } => return false,
+        };
+
+        if let Some(viewer) = self.history_viewer.as_mut() {
+            let history = ctx.dialog_history.lock().unwrap();
+            if !viewer.handle_key_down(key, &history.borrow()) {
+                self.history_viewer = None;
+            }
+            return true;
+        }
+
+        if key == DIALOG_HISTORY_KEY {
+            self.history_viewer = Some(DialogHistoryViewer::new());
+            return true;
+        }
+
+        if let Some(dialog_box) = self.dialog_box.as_mut() {
+            dialog_box.handle_event(ctx, event);
+            if dialog_box.is_finished() {
+                self.dialog_box = None;
+            }
+            return true;
+        }
+
+        false
+    }
 }
 
 impl Screen for OverworldScreen {
     fn draw(&mut self, ggez: &mut ggez::Context, ctx: GameContextRef<'_>) -> GameResult {
         graphics::clear(ggez, Color::BLACK);
-        self.room.as_mut().unwrap().draw(ggez, ctx)
+        self.room.as_mut().unwrap().draw(ggez, ctx)?;
+        if let Some(dialog_box) = &self.dialog_box {
+            dialog_box.draw(ggez, ctx)?;
+        }
+        if let Some(viewer) = &self.history_viewer {
+            viewer.draw(ggez, ctx)?;
+        }
+        Ok(())
     }
 
     fn update(&mut self, ggez: &mut ggez::Context, ctx: GameContextRef<'_>) -> GameResult {
-        self.room.as_mut().unwrap().update(ggez, ctx)
+        if self.is_room_paused() {
+            return Ok(());
+        }
+        let room = self.room.as_mut().unwrap();
+        room.update(ggez, ctx)?;
+        self.raised_events.extend(room.take_raised_events());
+        if let Some(request) = room.take_encounter() {
+            let screen = BattleScreen::new(request.encounter, Rc::new(Cell::new(None)));
+            self.start_battle(screen, Some(request.source));
+            return Ok(());
+        }
+        if let Some(next_room_name) = room.take_exit() {
+            self.enter_room(ctx, &next_room_name)?;
+        }
+        Ok(())
     }
 
     fn handle_event(&mut self, ggez: &mut ggez::Context, ctx: GameContextRef<'_>, event: UiEvent) {
+        if self.handle_overlay_event(ctx, event) || self.cutscenes > 0 {
+            return;
+        }
         self.room.as_mut().unwrap().handle_event(ggez, ctx, event)
     }
 }
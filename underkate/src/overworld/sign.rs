@@ -0,0 +1,102 @@
+use super::draw_order::YSorted;
+use super::hitbox::{Hitbox, Solid};
+use super::interaction::Interact;
+use super::move_trait::{Direction, HasMoveContext, MoveContext, Position};
+use crate::dialog::{Dialog, DialogFrame};
+use crate::geometry::{OverworldRect, ScreenPoint};
+use crate::graphics::texture::Texture;
+use crate::graphics::Draw;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use crate::script::Script;
+use ggez::{Context, GameResult};
+
+#[derive(Debug, Clone)]
+pub struct SignCreationParams {
+    pub position: Position,
+    pub hitbox: OverworldRect<f32>,
+    /// A sign without a texture is an invisible spot, which the player can walk through.
+    pub texture: Option<String>,
+    /// The string IDs of the dialog frames shown when the sign is examined.
+    pub text: Vec<&'static str>,
+}
+
+/// A sign or any other object which shows a dialog when examined.
+pub struct Sign {
+    texture: Option<Texture>,
+    hitbox: OverworldRect<f32>,
+    move_context: MoveContext,
+    dialog: Box<dyn Script>,
+}
+
+impl Sign {
+    pub fn new(params: &SignCreationParams, resources: &GlobalResourceStorage) -> Self {
+        let frames = params
+            .text
+            .iter()
+            .map(|text_id| DialogFrame::new(None, text_id))
+            .collect();
+        Self {
+            texture: params
+                .texture
+                .as_ref()
+                .map(|path| resources.get_cloned(path)),
+            hitbox: params.hitbox,
+            move_context: MoveContext {
+                position: params.position,
+                direction: Direction::Backward,
+            },
+            dialog: Box::new(Dialog::new(frames)),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.texture.is_some()
+    }
+}
+
+impl AsRef<MoveContext> for Sign {
+    fn as_ref(&self) -> &MoveContext {
+        &self.move_context
+    }
+}
+
+impl AsMut<MoveContext> for Sign {
+    fn as_mut(&mut self) -> &mut MoveContext {
+        &mut self.move_context
+    }
+}
+
+impl HasMoveContext for Sign {}
+
+impl Hitbox for Sign {
+    fn hitbox_at_origin(&self) -> OverworldRect<f32> {
+        self.hitbox
+    }
+}
+
+impl Solid for Sign {
+    fn is_solid(&self) -> bool {
+        self.is_visible()
+    }
+}
+
+impl Interact for Sign {
+    fn interaction_script_mut(&mut self) -> Option<&mut Box<dyn Script>> {
+        Some(&mut self.dialog)
+    }
+
+    fn can_interact(&self) -> bool {
+        true
+    }
+}
+
+impl Draw for Sign {
+    fn draw(&self, ctx: &mut Context, center_at: ScreenPoint<f32>) -> GameResult {
+        match &self.texture {
+            Some(texture) => texture.draw(ctx, center_at),
+            None => Ok(()),
+        }
+    }
+}
+
+impl YSorted for Sign {}
@@ -0,0 +1,170 @@
+use super::pass_map::polygon_pass_map::Polygon;
+use crate::flags::{self, Condition, Flags};
+use crate::geometry::OverworldRect;
+use crate::resources::{GlobalResourceStorage, ResourceStorageCloneExt};
+use crate::script::rust_script::RustScript;
+use crate::script::Script;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub enum Shape {
+    // Only built by `load_room!`, and the shipped zones are all polygons.
+    #[allow(dead_code)]
+    Rect(OverworldRect<f32>),
+    Polygon(Polygon),
+}
+
+impl Shape {
+    pub fn intersects(&self, rect: &OverworldRect<f32>) -> bool {
+        match self {
+            Self::Rect(shape) => shape.intersects(rect),
+            Self::Polygon(shape) => shape.intersects(rect),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum ActionParams {
+    // Only built by `load_room!`; the shipped triggers all run scripts.
+    #[allow(dead_code)]
+    Event(&'static str),
+    Script(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct CreationParams {
+    /// Unique within the room.
+    pub name: &'static str,
+    pub shape: Shape,
+    pub on_enter: Option<ActionParams>,
+    pub on_leave: Option<ActionParams>,
+    /// Fires once the player has stayed in the zone for the given time without leaving it.
+    pub on_stay: Option<(Duration, ActionParams)>,
+    /// Disables the trigger after it fires for the first time.
+    pub once: bool,
+    pub conditions: Vec<Condition>,
+}
+
+pub enum Action {
+    Event(&'static str),
+    Script(Box<dyn Script>),
+}
+
+impl Action {
+    fn new(params: ActionParams, resources: &GlobalResourceStorage) -> Self {
+        match params {
+            ActionParams::Event(name) => Self::Event(name),
+            ActionParams::Script(name) => {
+                let script: RustScript = resources.get_cloned(name);
+                Self::Script(Box::new(script))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TriggerKind {
+    Enter,
+    Leave,
+    Stay,
+}
+
+/// A zone which reacts to the player walking into it, out of it, or staying in it.
+pub struct Trigger {
+    name: &'static str,
+    shape: Shape,
+    on_enter: Option<Action>,
+    on_leave: Option<Action>,
+    on_stay: Option<(Duration, Action)>,
+    once: bool,
+    conditions: Vec<Condition>,
+    used: bool,
+    // How long the player has been in the zone, if they are in it.
+    occupied_for: Option<Duration>,
+}
+
+impl Trigger {
+    // A player who starts in the zone does not enter it.
+    pub fn new(
+        params: &CreationParams,
+        player_hitbox: &OverworldRect<f32>,
+        resources: &GlobalResourceStorage,
+    ) -> Self {
+        let occupied = params.shape.intersects(player_hitbox);
+        Self {
+            name: params.name,
+            shape: params.shape.clone(),
+            on_enter: params.on_enter.map(|action| Action::new(action, resources)),
+            on_leave: params.on_leave.map(|action| Action::new(action, resources)),
+            on_stay: params
+                .on_stay
+                .map(|(duration, action)| (duration, Action::new(action, resources))),
+            once: params.once,
+            conditions: params.conditions.clone(),
+            used: false,
+            occupied_for: if occupied { Some(Duration::ZERO) } else { None },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Tracks the player's hitbox and returns what fires this frame, if anything.
+    pub fn update(
+        &mut self,
+        player_hitbox: &OverworldRect<f32>,
+        time_slice: Duration,
+        flags: &Flags,
+    ) -> Option<TriggerKind> {
+        let occupied = self.shape.intersects(player_hitbox);
+        let fired = match (self.occupied_for, occupied) {
+            (None, true) => Some(TriggerKind::Enter),
+            (Some(_), false) => Some(TriggerKind::Leave),
+            (Some(before), true) => match &self.on_stay {
+                Some((duration, _)) if before < *duration && before + time_slice >= *duration => {
+                    Some(TriggerKind::Stay)
+                }
+                _ => None,
+            },
+            (None, false) => None,
+        };
+        self.occupied_for = match (self.occupied_for, occupied) {
+            (_, false) => None,
+            (None, true) => Some(Duration::ZERO),
+            (Some(before), true) => Some(before + time_slice),
+        };
+
+        let kind = fired.filter(|&kind| self.action(kind).is_some())?;
+        if self.used || !flags::all_hold(&self.conditions, flags) {
+            return None;
+        }
+        self.used = self.once;
+        Some(kind)
+    }
+
+    /// Whether the trigger is once-only and has already fired.
+    pub fn is_used(&self) -> bool {
+        self.used
+    }
+
+    pub fn mark_used(&mut self) {
+        self.used = true;
+    }
+
+    pub fn action_mut(&mut self, kind: TriggerKind) -> Option<&mut Action> {
+        match kind {
+            TriggerKind::Enter => self.on_enter.as_mut(),
+            TriggerKind::Leave => self.on_leave.as_mut(),
+            TriggerKind::Stay => self.on_stay.as_mut().map(|(_, action)| action),
+        }
+    }
+
+    fn action(&self, kind: TriggerKind) -> Option<&Action> {
+        match kind {
+            TriggerKind::Enter => self.on_enter.as_ref(),
+            TriggerKind::Leave => self.on_leave.as_ref(),
+            TriggerKind::Stay => self.on_stay.as_ref().map(|(_, action)| action),
+        }
+    }
+}
@@ -1,27 +1,26 @@
 use super::move_trait::{Move, Position};
+use super::movement::Movement;
 use crate::geometry::OverworldVector;
 use std::time::Duration;
 
 #[derive(Debug, Copy, Clone)]
 pub struct WalkInfo {
+    /// The velocity the entity wants to walk with, before any speed multipliers.
     pub velocity: OverworldVector<f32>,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum WalkState {
+    #[default]
     Still,
     Walking(WalkInfo),
 }
 
-impl Default for WalkState {
-    fn default() -> Self {
-        WalkState::Still
-    }
-}
-
 pub trait Walk: Move {
     fn walk_state(&self) -> WalkState;
     fn set_walk_state(&mut self, walk_state: WalkState);
+    fn movement(&self) -> &Movement;
+    fn movement_mut(&mut self) -> &mut Movement;
 
     fn start_walking(&mut self, walk_info: WalkInfo) {
         self.set_walk_state(WalkState::Walking(walk_info));
@@ -32,11 +31,14 @@ pub trait Walk: Move {
     }
 
     fn get_updated_position(&mut self, time_slice: Duration) -> Position {
-        let position_change = match self.walk_state() {
+        let target_velocity = match self.walk_state() {
             WalkState::Still => OverworldVector::zero(),
-            WalkState::Walking(WalkInfo { velocity, .. }) => velocity * time_slice.as_secs_f32(),
+            WalkState::Walking(WalkInfo { velocity, .. }) => velocity,
         };
+        let velocity = self
+            .movement_mut()
+            .update_velocity(target_velocity, time_slice);
 
-        self.position() + position_change
+        self.position() + velocity * time_slice.as_secs_f32()
     }
 }
@@ -1,12 +1,16 @@
+use crate::audio::Sound;
 use crate::graphics::texture::Texture;
-use crate::overworld::pass_map::BitmapPassMap;
+use crate::locale::Localization;
+use crate::overworld::pass_map::AnyPassMap;
 use crate::overworld::room::PartialCreationParams as RoomPartialCreationParams;
 use crate::script::rust_script::RustScript;
 use ggez::Context;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
-use underkate_tools::{load_pass_map, load_room, load_rust_script, load_texture};
+use underkate_tools::{
+    load_locales, load_pass_map, load_room, load_rust_script, load_sound, load_texture,
+};
 
 #[derive(Debug, Copy, Clone)]
 pub struct ResourceDoesNotExist<'a> {
@@ -37,7 +41,7 @@ pub trait ResourceStorageCloneExt<T: Clone>: ResourceStorage<T> {
 
 impl<T: Clone, S: ResourceStorage<T>> ResourceStorageCloneExt<T> for S {
     fn try_get_cloned<'a>(&self, name: &'a str) -> Result<T, ResourceDoesNotExist<'a>> {
-        self.try_get(name).map(Clone::clone)
+        self.try_get(name).cloned()
     }
 
     fn get_cloned(&self, name: &str) -> T {
@@ -48,8 +52,9 @@ impl<T: Clone, S: ResourceStorage<T>> ResourceStorageCloneExt<T> for S {
 pub struct GlobalResourceStorage {
     textures: HashMap<String, Texture>,
     room_partial_creation_params: HashMap<String, RoomPartialCreationParams>,
-    bitmap_pass_maps: HashMap<String, BitmapPassMap>,
+    pass_maps: HashMap<String, AnyPassMap>,
     rust_scripts: HashMap<String, RustScript>,
+    sounds: HashMap<String, Sound>,
 }
 
 impl GlobalResourceStorage {
@@ -57,8 +62,9 @@ impl GlobalResourceStorage {
         Self {
             textures: HashMap::new(),
             room_partial_creation_params: HashMap::new(),
-            bitmap_pass_maps: HashMap::new(),
+            pass_maps: HashMap::new(),
             rust_scripts: HashMap::new(),
+            sounds: HashMap::new(),
         }
     }
 }
@@ -81,8 +87,9 @@ macro_rules! resource_storage_impl {
 
 resource_storage_impl!(Texture as .textures);
 resource_storage_impl!(RoomPartialCreationParams as .room_partial_creation_params);
-resource_storage_impl!(BitmapPassMap as .bitmap_pass_maps);
+resource_storage_impl!(AnyPassMap as .pass_maps);
 resource_storage_impl!(RustScript as .rust_scripts);
+resource_storage_impl!(Sound as .sounds);
 
 macro_rules! use_texture {
     ($path:tt => $storage:expr, $ctx:expr) => {
@@ -97,12 +104,31 @@ macro_rules! use_room {
     };
 }
 
+// Rooms made in Tiled bring their own textures and pass map, generated from the map.
+macro_rules! use_tiled_room {
+    ($path:tt => $storage:expr, $ctx:expr) => {
+        let ctx: &mut Context = $ctx;
+        let (textures, (pass_map_name, pass_map)) = underkate_tools::load_tiled_room_assets!($path);
+        for (name, texture) in textures {
+            $storage.put(name, texture);
+        }
+        $storage.put(pass_map_name, pass_map);
+        use_room!($path => $storage);
+    };
+}
+
 macro_rules! use_pass_map {
     ($path:tt => $storage:expr) => {
         $storage.put(String::from($path), load_pass_map!($path));
     };
 }
 
+macro_rules! use_sound {
+    ($path:tt => $storage:expr) => {
+        $storage.put(String::from($path), load_sound!($path));
+    };
+}
+
 macro_rules! use_rust_script {
     ($path:tt => $storage:expr) => {
         $storage.put(String::from($path), load_rust_script!($path));
@@ -111,14 +137,32 @@ macro_rules! use_rust_script {
 
 pub fn make_global_storage(ctx: &mut Context) -> GlobalResourceStorage {
     let mut storage = GlobalResourceStorage::new();
-    use_texture!("textures/overworld/player/front" => storage, ctx);
-    use_texture!("textures/overworld/player/back" => storage, ctx);
-    use_texture!("textures/overworld/player/leftward" => storage, ctx);
-    use_texture!("textures/overworld/player/rightward" => storage, ctx);
-    use_texture!("textures/overworld/rooms/home/room/bg" => storage, ctx);
-    use_pass_map!("pass-maps/overworld/rooms/home/room" => storage);
+    use_texture!("overworld/player/front" => storage, ctx);
+    use_texture!("overworld/player/back" => storage, ctx);
+    use_texture!("overworld/player/leftward" => storage, ctx);
+    use_texture!("overworld/player/rightward" => storage, ctx);
+    use_texture!("overworld/player/look_around" => storage, ctx);
+    use_texture!("overworld/npcs/neighbour/left" => storage, ctx);
+    use_texture!("overworld/npcs/neighbour/right" => storage, ctx);
+    use_texture!("overworld/npcs/neighbour/forward" => storage, ctx);
+    use_texture!("overworld/npcs/neighbour/backward" => storage, ctx);
+    use_texture!("overworld/party/kit/left" => storage, ctx);
+    use_texture!("overworld/party/kit/right" => storage, ctx);
+    use_texture!("overworld/party/kit/forward" => storage, ctx);
+    use_texture!("overworld/party/kit/backward" => storage, ctx);
+    use_texture!("overworld/emotes/exclamation" => storage, ctx);
+    use_texture!("overworld/rooms/home/room/bg" => storage, ctx);
+    use_pass_map!("overworld/rooms/home/room" => storage);
     use_room!("home/room" => storage);
-    use_rust_script!("scripts/overworld/rooms/home/room/init" => storage);
+    use_tiled_room!("home/yard" => storage, ctx);
+    use_rust_script!("overworld/rooms/home/room/init" => storage);
+    use_rust_script!("overworld/rooms/home/room/save" => storage);
+    use_rust_script!("overworld/rooms/home/yard/crossroads" => storage);
+    use_sound!("overworld/footsteps/default" => storage);
 
     storage
 }
+
+pub fn make_localization() -> Localization {
+    load_locales!("locales")
+}
@@ -0,0 +1,184 @@
+use crate::flags::Flags;
+use crate::game_context::{GameContext, GameContextRef};
+use crate::geometry::OverworldRect;
+use crate::inventory::Inventory;
+use crate::overworld::follower::{self, Follower};
+use crate::overworld::move_trait::Direction;
+use crate::overworld::multiside::DirectionalTexturePaths;
+use crate::overworld::room::{CreationParams, PartialCreationParams, Room};
+use crate::overworld::room_state::RoomStateStore;
+use crate::resources::ResourceStorageCloneExt;
+use ggez::{filesystem, Context};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SAVE_FILE_NAME: &str = "save.toml";
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Encode(toml::ser::Error),
+    Decode(toml::de::Error),
+    /// The save refers to a room which the game doesn't have.
+    NoSuchRoom(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(fmt, "Failed to access the save file: {}", e),
+            Self::Encode(e) => write!(fmt, "Failed to encode the save: {}", e),
+            Self::Decode(e) => write!(fmt, "The save file is malformed: {}", e),
+            Self::NoSuchRoom(name) => write!(fmt, "The saved room `{}` does not exist", name),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<toml::ser::Error> for SaveError {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::Encode(e)
+    }
+}
+
+impl From<toml::de::Error> for SaveError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Decode(e)
+    }
+}
+
+/// A party member, saved as what it takes to create them again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowerData {
+    pub name: String,
+    pub textures: DirectionalTexturePaths,
+    /// `[left, top, right, bottom]`, relative to the follower's position.
+    pub hitbox: [f32; 4],
+    pub distance: f32,
+}
+
+impl From<&follower::CreationParams> for FollowerData {
+    fn from(params: &follower::CreationParams) -> Self {
+        let hitbox = params.hitbox;
+        Self {
+            name: params.name.clone(),
+            textures: params.textures.clone(),
+            hitbox: [hitbox.min.x, hitbox.min.y, hitbox.max.x, hitbox.max.y],
+            distance: params.distance,
+        }
+    }
+}
+
+impl From<FollowerData> for follower::CreationParams {
+    fn from(data: FollowerData) -> Self {
+        let [left, top, right, bottom] = data.hitbox;
+        Self {
+            name: data.name,
+            textures: data.textures,
+            hitbox: OverworldRect::new([left, top].into(), [right, bottom].into()),
+            distance: data.distance,
+        }
+    }
+}
+
+/// Everything needed to continue the game later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    pub room: String,
+    pub player_position: [f32; 2],
+    pub player_direction: Direction,
+    pub flags: Flags,
+    #[serde(default)]
+    pub inventory: Inventory,
+    /// The party members walking behind the player, in order.
+    #[serde(default)]
+    pub followers: Vec<FollowerData>,
+    pub rooms: RoomStateStore,
+}
+
+impl SaveData {
+    pub fn capture(ctx: GameContextRef<'_>) -> Self {
+        let screen = ctx.overworld_screen.lock().unwrap();
+        let screen = screen.borrow();
+        let room = screen.room();
+        Self {
+            room: String::from(room.name()),
+            player_position: room.player_position().to_array(),
+            player_direction: room.player_direction(),
+            flags: ctx.flags.lock().unwrap().borrow().clone(),
+            inventory: ctx.inventory.lock().unwrap().borrow().clone(),
+            followers: room
+                .followers()
+                .iter()
+                .map(|follower| FollowerData::from(follower.params()))
+                .collect(),
+            rooms: screen.room_states(),
+        }
+    }
+
+    /// Puts the player back into the saved room, with everything as it was.
+    pub fn restore(self, ctx: GameContextRef<'_>) -> Result<(), SaveError> {
+        let partial: PartialCreationParams = ctx
+            .global_resource_storage
+            .try_get_cloned(&self.room)
+            .map_err(|_| SaveError::NoSuchRoom(self.room.clone()))?;
+        let params = CreationParams::with_player_at(
+            partial,
+            self.player_position.into(),
+            self.player_direction,
+        );
+        let mut room = Room::new(params, ctx.global_resource_storage);
+        for data in self.followers {
+            let params = follower::CreationParams::from(data);
+            room.add_follower(Follower::new(&params, ctx.global_resource_storage));
+        }
+        *ctx.flags.lock().unwrap().borrow_mut() = self.flags;
+        *ctx.inventory.lock().unwrap().borrow_mut() = self.inventory;
+
+        let screen = ctx.overworld_screen.lock().unwrap();
+        let mut screen = screen.borrow_mut();
+        screen.set_room_states(self.rooms);
+        screen.load_room(ctx, room);
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let text = fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        // Going through a value puts plain values before tables, as TOML requires.
+        let text = toml::Value::try_from(self)?.to_string();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+pub fn save_path(ggez: &Context) -> PathBuf {
+    filesystem::user_data_dir(ggez).join(SAVE_FILE_NAME)
+}
+
+/// Saves the game once the current frame is over.
+pub fn save_game(context: &GameContext) {
+    context
+        .overworld_screen
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .request_save();
+}
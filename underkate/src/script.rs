@@ -3,6 +3,7 @@ pub mod rust_script;
 use crate::game_context::GameContext;
 use std::future::Future;
 use std::pin::Pin;
+use std::task::{Context, Poll};
 
 mod tag {
     #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -17,17 +18,22 @@ mod tag {
 }
 pub use tag::{EventHandle, EventHandleGenerator, ScriptHandle, ScriptHandleGenerator};
 
+/// A function which needs the ggez context, run by the runtime on its next update.
+pub type WithGgezFn = Box<dyn FnMut(&mut ggez::Context)>;
+
+// Part of the scripting API, which the scripts shipped so far only use some of.
+#[allow(dead_code)]
 pub trait Runtime {
     fn subscribe(&mut self, event: EventHandle, script: ScriptHandle);
     fn raise_event(&mut self, event: EventHandle);
+    /// Returns the event with the given name, so that scripts and data files can refer to it.
+    fn event_by_name(&mut self, name: &str) -> EventHandle;
     fn wake_event(&self, script: ScriptHandle) -> Option<EventHandle>;
-    fn start_script(&mut self, context: GameContext, script: &mut dyn Script);
-    fn run_with_ggez(
-        &mut self,
-        func: Box<dyn FnMut(&mut ggez::Context)>,
-    ) -> Pin<Box<dyn Future<Output = ()>>>;
+    fn start_script(&mut self, context: GameContext, script: &mut dyn Script) -> ScriptHandle;
+    fn is_running(&self, script: ScriptHandle) -> bool;
+    fn run_with_ggez(&mut self, func: WithGgezFn) -> Pin<Box<dyn Future<Output = ()>>>;
     fn update(&mut self, ctx: &mut ggez::Context);
-    fn wait_for_event(&mut self, event: EventHandle) -> Box<dyn Future<Output = ()>>;
+    fn wait_for_event(&mut self, event: EventHandle) -> Pin<Box<dyn Future<Output = ()>>>;
 }
 
 pub trait Script {
@@ -37,3 +43,24 @@ pub trait Script {
         context: GameContext,
     ) -> Pin<Box<dyn Future<Output = ()>>>;
 }
+
+pub struct WaitUntil<F> {
+    condition: F,
+}
+
+impl<F: FnMut() -> bool + Unpin> Future for WaitUntil<F> {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, _future_ctx: &mut Context<'_>) -> Poll<()> {
+        if (self.condition)() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Resolves once `condition` returns `true`. The condition is checked every time the script is
+/// polled, i.e. once per frame.
+pub fn wait_until<F: FnMut() -> bool + Unpin>(condition: F) -> WaitUntil<F> {
+    WaitUntil { condition }
+}
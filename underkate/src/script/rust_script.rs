@@ -18,7 +18,7 @@ impl RustScript {
 
 impl Script for RustScript {
     fn start(
-        self: &mut Self,
+        &mut self,
         script_handle: ScriptHandle,
         context: GameContext,
     ) -> Pin<Box<dyn Future<Output = ()>>> {
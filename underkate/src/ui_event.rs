@@ -5,3 +5,20 @@ pub enum UiEvent {
     KeyDown { key: KeyCode, mods: KeyMods },
     KeyUp { key: KeyCode, mods: KeyMods },
 }
+
+pub fn is_confirm_key(key: KeyCode) -> bool {
+    matches!(key, KeyCode::Z | KeyCode::Return)
+}
+
+pub fn is_cancel_key(key: KeyCode) -> bool {
+    matches!(key, KeyCode::X | KeyCode::Escape)
+}
+
+pub fn is_run_key(key: KeyCode) -> bool {
+    matches!(key, KeyCode::LShift | KeyCode::RShift)
+}
+
+pub const DIALOG_HISTORY_KEY: KeyCode = KeyCode::H;
+
+/// Switches to the next language wherever the player is.
+pub const LANGUAGE_KEY: KeyCode = KeyCode::L;
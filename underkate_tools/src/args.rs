@@ -41,14 +41,14 @@ pub fn parse_args(tokens: TokenStream) -> Result<Args, ParseError> {
 pub fn parse_list_args(tokens: TokenStream) -> ListArgs {
     #[derive(Debug)]
     enum State {
-        ReadingPath,
-        ReadingColon {
+        Path,
+        Colon {
             path: String,
         },
-        ReadingType {
+        Type {
             path: String,
         },
-        ReadingDelimiter {
+        Delimiter {
             path: String,
             resource_type: ResourceType,
         },
@@ -56,23 +56,23 @@ pub fn parse_list_args(tokens: TokenStream) -> ListArgs {
 
     let mut resource_specs = Vec::new();
 
-    let mut current_state = State::ReadingPath;
+    let mut current_state = State::Path;
     for token in tokens {
         match current_state {
-            State::ReadingPath => {
+            State::Path => {
                 let path_lit: LitStr = syn::parse(token.clone().into())
-                    .expect(&format!("Expected a resource path, got {:?}", &token));
+                    .unwrap_or_else(|_| panic!("Expected a resource path, got {:?}", &token));
                 let path = path_lit.value();
-                current_state = State::ReadingColon { path };
+                current_state = State::Colon { path };
             }
-            State::ReadingColon { path } => {
+            State::Colon { path } => {
                 if token.to_string() == ":" {
-                    current_state = State::ReadingType { path };
+                    current_state = State::Type { path };
                 } else {
                     panic!("Expected a colon (':'), got {:?}", token);
                 }
             }
-            State::ReadingType { path } => {
+            State::Type { path } => {
                 let resource_type = match &token.to_string() as &str {
                     "texture" => ResourceType::Texture,
                     "pass_map" => ResourceType::PassMap,
@@ -80,18 +80,18 @@ pub fn parse_list_args(tokens: TokenStream) -> ListArgs {
                     "room" => ResourceType::Room,
                     x => panic!("Invalid resource type {:?}", x),
                 };
-                current_state = State::ReadingDelimiter {
+                current_state = State::Delimiter {
                     path,
                     resource_type,
                 };
             }
-            State::ReadingDelimiter {
+            State::Delimiter {
                 path,
                 resource_type,
             } => {
                 if token.to_string() == ";" {
                     resource_specs.push((path, resource_type));
-                    current_state = State::ReadingPath;
+                    current_state = State::Path;
                 } else {
                     panic!("Expected a semicolon (';'), got {:?}", token);
                 }
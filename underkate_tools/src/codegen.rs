@@ -13,7 +13,7 @@ fn mangle_path(path: &str) -> String {
             mangled.push(c);
         } else {
             mangled.push('_');
-            mangled.push_str(&mut (c as u32).to_string());
+            mangled.push_str(&(c as u32).to_string());
             mangled.push('x');
         }
     }
@@ -24,13 +24,13 @@ fn make_name_safe(name: &str) -> String {
     let mut safe_name = String::with_capacity(name.len());
     let mut chars = name.chars();
     let first_char = chars.next().expect("Resource or directory name is empty");
-    if !('a'..='z').contains(&first_char) {
+    if !first_char.is_ascii_lowercase() {
         panic!("Invalid resource or directory name");
     }
     safe_name.push(first_char);
 
     for c in chars {
-        if ('a'..='z').contains(&c) {
+        if c.is_ascii_lowercase() {
             safe_name.push(c);
         } else if c == '-' {
             safe_name.push('_');
@@ -136,7 +136,7 @@ impl DirectoryStruct {
 
             #(#subdir_tokens)*
         };
-        tokens.into()
+        tokens
     }
 
     pub fn codegen(&self) -> TokenStream {
@@ -150,18 +150,16 @@ fn add_resource_by_path(
     target: &mut DirectoryStruct,
 ) {
     if let &[leaf_component] = path_components {
-        if let Some(_) = target
+        if target
             .resources
             .insert(String::from(leaf_component), resource)
+            .is_some()
         {
             panic!("Duplicate resource {:?}", path_components);
         }
     } else {
         if let Some((&head, tail)) = path_components.split_first() {
-            let subdir = target
-                .subdirectories
-                .entry(String::from(head))
-                .or_insert_with(|| DirectoryStruct::new());
+            let subdir = target.subdirectories.entry(String::from(head)).or_default();
             add_resource_by_path(tail, resource, subdir);
         }
     }
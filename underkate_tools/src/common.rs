@@ -1,3 +1,7 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde::Deserialize;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ResourceType {
     Texture,
@@ -12,7 +16,88 @@ impl ResourceType {
             Self::Texture => "crate::graphics::texture::Texture",
             Self::Room => "crate::overworld::room::PartialCreationParams",
             Self::RustScript => "crate::script::rust_script::RustScript",
-            Self::PassMap => "crate::overworld::pass_map::BitmapPassMap",
+            Self::PassMap => "crate::overworld::pass_map::AnyPassMap",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Rect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl Rect {
+    pub fn codegen(&self) -> TokenStream {
+        let Rect {
+            left,
+            top,
+            right,
+            bottom,
+        } = self;
+        quote! {
+            crate::geometry::OverworldRect::<f32>::new([#left, #top].into(), [#right, #bottom].into())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Polygon {
+    pub points: Vec<[f32; 2]>,
+}
+
+impl Polygon {
+    pub fn codegen(&self) -> TokenStream {
+        if self.points.len() < 3 {
+            panic!("A polygon must have at least 3 points");
+        }
+        let xs = self.points.iter().map(|[x, _y]| x);
+        let ys = self.points.iter().map(|[_x, y]| y);
+        quote! {
+            crate::overworld::pass_map::polygon_pass_map::Polygon::new(vec![
+                #(crate::geometry::OverworldPoint::<f32>::new(#xs, #ys)),*
+            ])
         }
     }
 }
+
+/// A condition on a flag: `{ flag = "name" }` holds if the flag is set, and `unset`, `at_least`
+/// and `below` test it differently.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlagCondition {
+    pub flag: String,
+    #[serde(default)]
+    pub unset: bool,
+    pub at_least: Option<i64>,
+    pub below: Option<i64>,
+}
+
+impl FlagCondition {
+    pub fn codegen(&self) -> TokenStream {
+        let flag = &self.flag;
+        let test = match (self.unset, self.at_least, self.below) {
+            (false, None, None) => quote! { Set },
+            (true, None, None) => quote! { Unset },
+            (false, Some(min), None) => quote! { AtLeast(#min) },
+            (false, None, Some(max)) => quote! { Below(#max) },
+            _ => panic!(
+                "Condition on flag `{}` must use at most one of `unset`, `at_least` and `below`",
+                flag
+            ),
+        };
+        quote! {
+            crate::flags::Condition {
+                flag: #flag,
+                test: crate::flags::FlagTest::#test,
+            }
+        }
+    }
+}
+
+pub fn codegen_conditions(conditions: &[FlagCondition]) -> TokenStream {
+    let conditions = conditions.iter().map(FlagCondition::codegen);
+    quote! { vec![#(#conditions),*] }
+}
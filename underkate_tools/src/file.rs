@@ -1,7 +1,7 @@
 use std::fmt::Display;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn read_file<P: AsRef<Path> + Display>(path: P) -> String {
     let error_string = format!("Failed to open file {}", path);
@@ -12,3 +12,27 @@ pub fn read_file<P: AsRef<Path> + Display>(path: P) -> String {
         .expect(&error_string);
     buf
 }
+
+/// Lists the files in the directory and its subdirectories whose names satisfy `matches`, sorted
+/// by path.
+pub fn find_files(dir: &Path, matches: &dyn Fn(&str) -> bool) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let entries =
+        fs::read_dir(dir).unwrap_or_else(|e| panic!("Failed to list {}: {}", dir.display(), e));
+    for entry in entries {
+        let path = entry
+            .unwrap_or_else(|e| panic!("Failed to list {}: {}", dir.display(), e))
+            .path();
+        if path.is_dir() {
+            found.append(&mut find_files(&path, matches));
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(matches)
+        {
+            found.push(path);
+        }
+    }
+    found.sort();
+    found
+}
@@ -2,15 +2,18 @@ mod args;
 mod codegen;
 mod common;
 mod file;
+mod locale;
 mod pass_map;
 mod room;
 mod rust_script;
+mod sound;
 mod texture;
+mod tiled;
 
 use crate::args::{parse_args, parse_list_args};
 use proc_macro::TokenStream;
 
-const ASSETS_DIR: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets");
+const ASSETS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets");
 
 #[proc_macro]
 pub fn load_texture(tokens: TokenStream) -> TokenStream {
@@ -24,18 +27,36 @@ pub fn load_room(tokens: TokenStream) -> TokenStream {
     room::load_room(&args)
 }
 
+#[proc_macro]
+pub fn load_tiled_room_assets(tokens: TokenStream) -> TokenStream {
+    let args = parse_args(tokens).unwrap();
+    room::load_tiled_room_assets(&args)
+}
+
 #[proc_macro]
 pub fn load_pass_map(tokens: TokenStream) -> TokenStream {
     let args = parse_args(tokens).unwrap();
     pass_map::load_pass_map(&args)
 }
 
+#[proc_macro]
+pub fn load_sound(tokens: TokenStream) -> TokenStream {
+    let args = parse_args(tokens).unwrap();
+    sound::load_sound(&args)
+}
+
 #[proc_macro]
 pub fn load_rust_script(tokens: TokenStream) -> TokenStream {
     let args = parse_args(tokens).unwrap();
     rust_script::load_rust_script(&args)
 }
 
+#[proc_macro]
+pub fn load_locales(tokens: TokenStream) -> TokenStream {
+    let args = parse_args(tokens).unwrap();
+    locale::load_locales(&args)
+}
+
 #[proc_macro]
 pub fn load_assets(tokens: TokenStream) -> TokenStream {
     let args = parse_list_args(tokens);
@@ -0,0 +1,397 @@
+use crate::args::Args;
+use crate::file::{find_files, read_file};
+use crate::ASSETS_DIR;
+use proc_macro::TokenStream;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Deserialize)]
+struct Manifest {
+    default: String,
+    locales: Vec<String>,
+    /// The string IDs the engine refers to. The ones used by rooms and scripts are collected from
+    /// them instead.
+    keys: BTreeSet<String>,
+}
+
+#[derive(Deserialize)]
+struct LocaleManifest {
+    name: String,
+    fallback: Option<String>,
+    plural_rule: PluralRule,
+    #[serde(rename = "strings")]
+    strings_path: String,
+}
+
+#[derive(Deserialize, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
+enum PluralRule {
+    OneOther,
+    EastSlavic,
+}
+
+impl PluralRule {
+    fn required_categories(&self) -> &'static [&'static str] {
+        match self {
+            Self::OneOther => &["one", "other"],
+            Self::EastSlavic => &["one", "few", "many", "other"],
+        }
+    }
+
+    fn variant_name(&self) -> &'static str {
+        match self {
+            Self::OneOther => "OneOther",
+            Self::EastSlavic => "EastSlavic",
+        }
+    }
+}
+
+const PLURAL_CATEGORIES: [&str; 4] = ["one", "few", "many", "other"];
+const PLURAL_MARKER: &str = "plural";
+
+enum Entry {
+    Simple(String),
+    Plural(BTreeMap<String, String>),
+}
+
+struct Locale {
+    id: String,
+    manifest: LocaleManifest,
+    strings: BTreeMap<String, Entry>,
+}
+
+// A table marked with `plural = true` is a set of plural forms; any other table is a group of
+// keys, which gets flattened into dotted string IDs.
+fn as_plural_forms(key: &str, table: &toml::value::Table) -> Option<BTreeMap<String, String>> {
+    if table.get(PLURAL_MARKER) != Some(&toml::Value::Boolean(true)) {
+        return None;
+    }
+    let forms = table
+        .iter()
+        .filter(|(name, _)| *name != PLURAL_MARKER)
+        .map(|(category, value)| {
+            let is_category = PLURAL_CATEGORIES.contains(&category.as_str());
+            match value {
+                toml::Value::String(form) if is_category => (category.clone(), form.clone()),
+                _ => panic!("Invalid plural form `{}` of string `{}`", category, key),
+            }
+        })
+        .collect();
+    Some(forms)
+}
+
+fn flatten_strings(prefix: &str, table: toml::value::Table, target: &mut BTreeMap<String, Entry>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            toml::Value::String(text) => {
+                target.insert(key, Entry::Simple(text));
+            }
+            toml::Value::Table(subtable) => match as_plural_forms(&key, &subtable) {
+                Some(forms) => {
+                    target.insert(key, Entry::Plural(forms));
+                }
+                None => flatten_strings(&key, subtable, target),
+            },
+            other => panic!("Invalid value for string `{}`: {}", key, other),
+        }
+    }
+}
+
+fn read_locale(locales_dir: &str, id: &str) -> Locale {
+    let dir_full_path = format!("{}/{}", locales_dir, id);
+    let manifest_full_path = format!("{}/locale.toml", dir_full_path);
+
+    let manifest: LocaleManifest =
+        toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+
+    let strings_full_path = format!("{}/{}", dir_full_path, manifest.strings_path);
+    let strings_table: toml::value::Table =
+        toml::from_str(&read_file(&strings_full_path)).expect("Failed to parse strings file");
+    let mut strings = BTreeMap::new();
+    flatten_strings("", strings_table, &mut strings);
+
+    Locale {
+        id: String::from(id),
+        manifest,
+        strings,
+    }
+}
+
+/// Encounter IDs become string IDs with this prefix, which are the titles of the encounters.
+const ENCOUNTER_PREFIX: &str = "battle.encounters.";
+
+// Sign and pickup texts and encounter titles, which are the string IDs room manifests refer to.
+fn collect_room_references(manifest: &toml::Value, references: &mut BTreeSet<String>) {
+    let entries = |section: &str| {
+        manifest
+            .get(section)
+            .and_then(toml::Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+    for sign in entries("signs") {
+        if let Some(text) = sign.get("text").and_then(toml::Value::as_array) {
+            references.extend(
+                text.iter()
+                    .filter_map(toml::Value::as_str)
+                    .map(String::from),
+            );
+        }
+    }
+    for pickup in entries("pickups") {
+        if let Some(text) = pickup.get("text").and_then(toml::Value::as_str) {
+            references.insert(String::from(text));
+        }
+    }
+    let zone_groups = entries("encounter_zones").into_iter().flat_map(|zone| {
+        zone.get("groups")
+            .and_then(toml::Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    });
+    for entry in entries("enemies").into_iter().chain(zone_groups) {
+        if let Some(encounter) = entry.get("encounter").and_then(toml::Value::as_str) {
+            references.insert(format!("{}{}", ENCOUNTER_PREFIX, encounter));
+        }
+    }
+}
+
+// The calls whose string literal arguments are string IDs once prefixed, which is how scripts
+// refer to them: dialog frames, and encounters started by `battle::start`.
+const REFERRING_CALLS: [(&str, &str, &str); 3] = [
+    ("DialogFrame", "new", ""),
+    ("DialogFrame", "with_count", ""),
+    ("battle", "start", ENCOUNTER_PREFIX),
+];
+
+fn collect_script_references(tokens: TokenStream2, references: &mut BTreeSet<String>) {
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    for (index, token) in tokens.iter().enumerate() {
+        let group = match token {
+            TokenTree::Group(group) => group,
+            _ => continue,
+        };
+        let path: Vec<_> = tokens[index.saturating_sub(4)..index]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let call = REFERRING_CALLS.iter().find(|(owner, function, _)| {
+            path.len() == 4 && path[..3] == [*owner, ":", ":"] && path[3] == *function
+        });
+        if let Some((_, _, prefix)) = call {
+            collect_string_literals(group.stream(), prefix, references);
+        } else {
+            collect_script_references(group.stream(), references);
+        }
+    }
+}
+
+fn collect_string_literals(tokens: TokenStream2, prefix: &str, references: &mut BTreeSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Literal(literal) => {
+                if let syn::Lit::Str(string) = syn::Lit::new(literal) {
+                    references.insert(format!("{}{}", prefix, string.value()));
+                }
+            }
+            TokenTree::Group(group) => collect_string_literals(group.stream(), prefix, references),
+            _ => {}
+        }
+    }
+}
+
+/// Collects the string IDs referred to by the room manifests and the scripts.
+fn collect_references() -> BTreeSet<String> {
+    let mut references = BTreeSet::new();
+    let rooms_dir = format!("{}/rooms", ASSETS_DIR);
+    for path in find_files(Path::new(&rooms_dir), &|name| name == "room.toml") {
+        let path = path.to_str().expect("Path is not valid UTF-8");
+        let manifest = toml::from_str(&read_file(path))
+            .unwrap_or_else(|e| panic!("Failed to parse {}: {}", path, e));
+        collect_room_references(&manifest, &mut references);
+    }
+    let scripts_dir = format!("{}/scripts", ASSETS_DIR);
+    for path in find_files(Path::new(&scripts_dir), &|name| name.ends_with(".rs")) {
+        let path = path.to_str().expect("Path is not valid UTF-8");
+        let tokens = read_file(path)
+            .parse()
+            .unwrap_or_else(|e| panic!("Failed to parse {}: {}", path, e));
+        collect_script_references(tokens, &mut references);
+    }
+    references
+}
+
+// Panics if the fallback chain of the locale is broken.
+fn check_fallback_chain(locale: &Locale, locales: &HashMap<&str, &Locale>) {
+    let mut chain = vec![locale];
+    let mut current = locale;
+    while let Some(fallback) = &current.manifest.fallback {
+        let next = *locales.get(fallback.as_str()).unwrap_or_else(|| {
+            panic!(
+                "Locale `{}` falls back to unknown locale `{}`",
+                current.id, fallback
+            )
+        });
+        if chain.iter().any(|visited| visited.id == next.id) {
+            panic!("Fallback cycle detected at locale `{}`", next.id);
+        }
+        chain.push(next);
+        current = next;
+    }
+}
+
+// Returns a human-readable report of every problem found, or `None` if all locales are valid.
+// `keys` are all the string IDs the game refers to.
+fn validate(manifest: &Manifest, locales: &[Locale], keys: &BTreeSet<String>) -> Option<String> {
+    let by_id: HashMap<&str, &Locale> = locales.iter().map(|l| (l.id.as_str(), l)).collect();
+    if !by_id.contains_key(manifest.default.as_str()) {
+        panic!(
+            "Default locale `{}` is not listed in `locales`",
+            manifest.default
+        );
+    }
+
+    let mut report = String::new();
+    for locale in locales {
+        check_fallback_chain(locale, &by_id);
+        // Fallbacks only cover up for keys at runtime; every locale file must define every key.
+        let missing: Vec<_> = keys
+            .iter()
+            .filter(|key| !locale.strings.contains_key(*key))
+            .collect();
+        let unused: Vec<_> = locale
+            .strings
+            .keys()
+            .filter(|key| !keys.contains(*key))
+            .collect();
+        let incomplete_plurals: Vec<_> = locale
+            .strings
+            .iter()
+            .filter_map(|(key, entry)| match entry {
+                Entry::Plural(forms) => {
+                    let required = locale.manifest.plural_rule.required_categories();
+                    let lacking: Vec<_> = required
+                        .iter()
+                        .filter(|category| !forms.contains_key(**category))
+                        .collect();
+                    if lacking.is_empty() {
+                        None
+                    } else {
+                        Some(format!("{} (lacks {:?})", key, lacking))
+                    }
+                }
+                Entry::Simple(_) => None,
+            })
+            .collect();
+
+        if missing.is_empty() && unused.is_empty() && incomplete_plurals.is_empty() {
+            continue;
+        }
+        writeln!(report, "Locale `{}`:", locale.id).unwrap();
+        for key in missing {
+            writeln!(report, "    missing key: {}", key).unwrap();
+        }
+        for key in unused {
+            writeln!(report, "    unused key: {}", key).unwrap();
+        }
+        for description in incomplete_plurals {
+            writeln!(report, "    incomplete plural forms: {}", description).unwrap();
+        }
+    }
+
+    if report.is_empty() {
+        None
+    } else {
+        Some(report)
+    }
+}
+
+fn codegen_entry(entry: &Entry) -> proc_macro2::TokenStream {
+    match entry {
+        Entry::Simple(text) => quote! {
+            crate::locale::LocalizedString::Simple(#text)
+        },
+        Entry::Plural(forms) => {
+            let optional_form = |category| match forms.get(category) {
+                Some(form) => quote! { Some(#form) },
+                None => quote! { None },
+            };
+            let one = optional_form("one");
+            let few = optional_form("few");
+            let many = optional_form("many");
+            let other = &forms["other"];
+            quote! {
+                crate::locale::LocalizedString::Plural(crate::locale::PluralForms {
+                    one: #one,
+                    few: #few,
+                    many: #many,
+                    other: #other,
+                })
+            }
+        }
+    }
+}
+
+fn codegen_locale(locale: &Locale) -> proc_macro2::TokenStream {
+    let id = &locale.id;
+    let name = &locale.manifest.name;
+    let fallback = match &locale.manifest.fallback {
+        Some(fallback) => quote! { Some(#fallback) },
+        None => quote! { None },
+    };
+    let plural_rule = format_ident!("{}", locale.manifest.plural_rule.variant_name());
+    let keys: Vec<_> = locale.strings.keys().collect();
+    let entries: Vec<_> = locale.strings.values().map(codegen_entry).collect();
+
+    quote! {
+        crate::locale::Locale {
+            id: #id,
+            name: #name,
+            fallback: #fallback,
+            plural_rule: crate::locale::PluralRule::#plural_rule,
+            strings: vec![
+                #((#keys, #entries)),*
+            ].into_iter().collect(),
+        }
+    }
+}
+
+pub fn load_locales(args: &Args) -> TokenStream {
+    let locales_dir = format!("{}/{}", ASSETS_DIR, args.path);
+    let manifest_full_path = format!("{}/locales.toml", locales_dir);
+
+    let manifest: Manifest =
+        toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+
+    let locales: Vec<_> = manifest
+        .locales
+        .iter()
+        .map(|id| read_locale(&locales_dir, id))
+        .collect();
+
+    let mut keys = collect_references();
+    keys.extend(manifest.keys.iter().cloned());
+    if let Some(report) = validate(&manifest, &locales, &keys) {
+        panic!("Locale validation failed:\n{}", report);
+    }
+
+    let default = &manifest.default;
+    let locale_constructors = locales.iter().map(codegen_locale);
+
+    (quote! {
+        crate::locale::Localization::new(
+            vec![#(#locale_constructors),*],
+            #default,
+        )
+    })
+    .into()
+}
@@ -1,46 +1,335 @@
 use crate::args::Args;
+use crate::common::{Polygon, Rect};
 use crate::file::read_file;
 use crate::ASSETS_DIR;
 use image::io::Reader as ImageReader;
+use image::GrayImage;
 use proc_macro::TokenStream as TokenStream1;
-use proc_macro2::{Literal, TokenTree};
+use proc_macro2::{Literal, TokenStream as TokenStream2, TokenTree};
 use quote::quote;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 
 #[derive(Deserialize)]
-struct Manifest {
-    #[serde(rename = "image")]
-    image_path: String,
-    scale: f32,
+#[serde(tag = "type")]
+#[serde(rename_all = "lowercase")]
+enum Manifest {
+    Bitmap {
+        #[serde(rename = "image")]
+        image_path: String,
+        scale: f32,
+        #[serde(default)]
+        terrain: BTreeMap<String, TerrainClass>,
+    },
+    Polygons {
+        bounds: Rect,
+        #[serde(default)]
+        rects: Vec<Rect>,
+        #[serde(default)]
+        polygons: Vec<Polygon>,
+    },
+    Composite {
+        sources: Vec<String>,
+    },
 }
 
-pub fn load_pass_map(args: &Args) -> TokenStream1 {
-    let dir_full_path = format!("{}/{}", ASSETS_DIR, args.path);
-    let manifest_full_path = format!("{}/pass-map.toml", dir_full_path);
+/// The class named `default` covers every free pixel not claimed by another class, so it has no
+/// luma of its own.
+const DEFAULT_TERRAIN_CLASS: &str = "default";
 
-    let manifest: Manifest =
-        toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+#[derive(Deserialize)]
+pub struct TerrainClass {
+    luma: Option<u8>,
+    #[serde(default = "default_speed")]
+    speed: f32,
+    on_enter: Option<String>,
+    on_leave: Option<String>,
+    footsteps: Option<String>,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+impl TerrainClass {
+    fn codegen(&self, name: &str) -> TokenStream2 {
+        let optional = |value: &Option<String>| match value {
+            Some(value) => quote! { Some(#value) },
+            None => quote! { None },
+        };
+        let speed = self.speed;
+        let enter_event = optional(&self.on_enter);
+        let leave_event = optional(&self.on_leave);
+        let footsteps = optional(&self.footsteps);
+        quote! {
+            crate::overworld::pass_map::TerrainClass {
+                name: #name,
+                speed_factor: #speed,
+                enter_event: #enter_event,
+                leave_event: #leave_event,
+                footsteps: #footsteps,
+            }
+        }
+    }
+}
+
+pub fn codegen_terrain_palette(terrain: &BTreeMap<String, TerrainClass>) -> TokenStream2 {
+    let default_class = TerrainClass {
+        luma: None,
+        speed: 1.0,
+        on_enter: None,
+        on_leave: None,
+        footsteps: None,
+    };
+    let mut classes = vec![(
+        DEFAULT_TERRAIN_CLASS,
+        terrain.get(DEFAULT_TERRAIN_CLASS).unwrap_or(&default_class),
+    )];
+    let mut class_by_luma = [0u8; 256];
+    for (name, class) in terrain {
+        if class.speed < 0.0 {
+            panic!("Terrain class `{}` has a negative speed", name);
+        }
+        if name == DEFAULT_TERRAIN_CLASS {
+            if class.luma.is_some() {
+                panic!("The default terrain class cannot have a luma");
+            }
+            continue;
+        }
+        let luma = match class.luma {
+            Some(0) => panic!("Terrain class `{}` uses luma 0, which is solid", name),
+            Some(luma) => luma as usize,
+            None => panic!("Terrain class `{}` has no luma", name),
+        };
+        if class_by_luma[luma] != 0 {
+            let (other, _) = classes[class_by_luma[luma] as usize];
+            panic!(
+                "Terrain classes `{}` and `{}` share luma {}",
+                other, name, luma
+            );
+        }
+        if classes.len() > u8::MAX as usize {
+            panic!("Too many terrain classes");
+        }
+        class_by_luma[luma] = classes.len() as u8;
+        classes.push((name, class));
+    }
+
+    let classes = classes.iter().map(|(name, class)| class.codegen(name));
+    quote! {
+        crate::overworld::pass_map::terrain::TerrainPalette::new(
+            &[#(#classes),*],
+            &[#(#class_by_luma),*],
+        )
+    }
+}
+
+/// Builds a summed-area table of occupied (black) pixels: the entry at `(x, y)` is the number of
+/// occupied pixels above and to the left of it, so the table is one larger than the image in each
+/// dimension. Entries are stored row by row.
+fn occupancy_table(image: &GrayImage) -> Vec<u32> {
+    let table_width = image.width() as usize + 1;
+    let table_height = image.height() as usize + 1;
+    let mut table = vec![0u32; table_width * table_height];
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let (x, y) = (x as usize, y as usize);
+        let occupied = (pixel.0[0] == 0) as u32;
+        table[(y + 1) * table_width + x + 1] =
+            occupied + table[y * table_width + x + 1] + table[(y + 1) * table_width + x]
+                - table[y * table_width + x];
+    }
+    table
+}
+
+/// Serializes the table as little-endian `u16`s if no count can exceed `u16::MAX`, which halves its
+/// size for small images, and as `u32`s otherwise. `Bitmap::new` applies the same rule.
+fn occupancy_table_bytes(image: &GrayImage) -> Vec<u8> {
+    let table = occupancy_table(image);
+    let pixels = image.width() as u64 * image.height() as u64;
+    if pixels <= u16::MAX as u64 {
+        table
+            .iter()
+            .flat_map(|&entry| (entry as u16).to_le_bytes())
+            .collect()
+    } else {
+        table.iter().flat_map(|entry| entry.to_le_bytes()).collect()
+    }
+}
 
-    let image = ImageReader::open(format!("{}/{}", dir_full_path, manifest.image_path))
-        .expect("Failed to open pass map image file")
-        .decode()
-        .expect("Failed to decode pass map image file")
-        .into_luma8();
+pub fn codegen_bitmap_pass_map(
+    image: &GrayImage,
+    scale: f32,
+    terrain: &BTreeMap<String, TerrainClass>,
+) -> TokenStream2 {
     let width = image.width() as usize;
     let height = image.height() as usize;
-    let scale = manifest.scale;
-    let image_bytes = &image as &[u8];
+    let image_bytes = image as &[u8];
     let image_bytes_lit = TokenTree::Literal(Literal::byte_string(image_bytes));
+    let table_bytes_lit = TokenTree::Literal(Literal::byte_string(&occupancy_table_bytes(image)));
+    let palette = codegen_terrain_palette(terrain);
 
-    let result = quote! {
-        crate::overworld::pass_map::BitmapPassMap::new(
-            crate::overworld::pass_map::bitmap_pass_map::Bitmap::new(
-                #width,
-                #height,
-                #image_bytes_lit,
-            ),
-            #scale,
+    quote! {
+        crate::overworld::pass_map::AnyPassMap::Bitmap(
+            crate::overworld::pass_map::BitmapPassMap::new(
+                crate::overworld::pass_map::bitmap_pass_map::Bitmap::new(
+                    #width,
+                    #height,
+                    #image_bytes_lit,
+                    #table_bytes_lit,
+                ),
+                #scale,
+                #palette,
+            )
         )
-    };
-    result.into()
+    }
+}
+
+// `chain` holds the pass maps currently being generated, so that cyclic composites are reported.
+fn codegen_pass_map(path: &str, chain: &mut Vec<String>) -> TokenStream2 {
+    if chain.iter().any(|visited| visited == path) {
+        panic!(
+            "Composite pass map `{}` includes itself through {:?}",
+            path, chain
+        );
+    }
+
+    let dir_full_path = format!("{}/pass-maps/{}", ASSETS_DIR, path);
+    let manifest_full_path = format!("{}/pass-map.toml", dir_full_path);
+
+    let mut manifest: toml::Value =
+        toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+    // Pass maps written before there were other kinds have no `type`.
+    if let toml::Value::Table(table) = &mut manifest {
+        table
+            .entry("type")
+            .or_insert_with(|| toml::Value::String(String::from("bitmap")));
+    }
+    let manifest: Manifest = manifest.try_into().expect("Failed to parse manifest file");
+
+    match manifest {
+        Manifest::Bitmap {
+            image_path,
+            scale,
+            terrain,
+        } => {
+            let image = ImageReader::open(format!("{}/{}", dir_full_path, image_path))
+                .expect("Failed to open pass map image file")
+                .decode()
+                .expect("Failed to decode pass map image file")
+                .into_luma8();
+            codegen_bitmap_pass_map(&image, scale, &terrain)
+        }
+        Manifest::Polygons {
+            bounds,
+            rects,
+            polygons,
+        } => {
+            let bounds = bounds.codegen();
+            let rects = rects.iter().map(Rect::codegen);
+            let polygons = polygons.iter().map(Polygon::codegen);
+            quote! {
+                crate::overworld::pass_map::AnyPassMap::Polygon(
+                    crate::overworld::pass_map::PolygonPassMap::new(
+                        #bounds,
+                        vec![#(#rects),*],
+                        vec![#(#polygons),*],
+                    )
+                )
+            }
+        }
+        Manifest::Composite { sources } => {
+            if sources.is_empty() {
+                panic!("Composite pass map `{}` has no sources", path);
+            }
+            chain.push(String::from(path));
+            let sources: Vec<_> = sources
+                .iter()
+                .map(|source| codegen_pass_map(source, chain))
+                .collect();
+            chain.pop();
+            quote! {
+                crate::overworld::pass_map::AnyPassMap::Composite(
+                    crate::overworld::pass_map::CompositePassMap::new(vec![#(#sources),*])
+                )
+            }
+        }
+    }
+}
+
+pub fn load_pass_map(args: &Args) -> TokenStream1 {
+    codegen_pass_map(&args.path, &mut Vec::new()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{occupancy_table, occupancy_table_bytes};
+    use image::{GrayImage, Luma};
+
+    fn pseudo_random_image(width: u32, height: u32, seed: u32) -> GrayImage {
+        let mut state = seed;
+        GrayImage::from_fn(width, height, |_, _| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            if (state >> 16) % 3 == 0 {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        })
+    }
+
+    fn count_by_scan(image: &GrayImage, x: (u32, u32), y: (u32, u32)) -> u32 {
+        let mut count = 0;
+        for pixel_y in y.0..y.1 {
+            for pixel_x in x.0..x.1 {
+                if image.get_pixel(pixel_x, pixel_y).0[0] == 0 {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn table_matches_scan_for_every_rect() {
+        for &(width, height, seed) in &[(1, 1, 1), (7, 5, 2), (13, 11, 3), (16, 1, 4), (1, 9, 5)] {
+            let image = pseudo_random_image(width, height, seed);
+            let table = occupancy_table(&image);
+            let table_width = width as usize + 1;
+            let at = |x: u32, y: u32| table[y as usize * table_width + x as usize];
+            for min_y in 0..=height {
+                for max_y in min_y..=height {
+                    for min_x in 0..=width {
+                        for max_x in min_x..=width {
+                            let from_table = at(max_x, max_y) + at(min_x, min_y)
+                                - at(min_x, max_y)
+                                - at(max_x, min_y);
+                            let scanned = count_by_scan(&image, (min_x, max_x), (min_y, max_y));
+                            assert_eq!(from_table, scanned);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn table_has_a_zero_border() {
+        let image = GrayImage::from_pixel(4, 3, Luma([0]));
+        let table = occupancy_table(&image);
+        assert_eq!(table.len(), 5 * 4);
+        assert!(table[..5].iter().all(|&entry| entry == 0));
+        assert!(table.iter().step_by(5).all(|&entry| entry == 0));
+        assert_eq!(*table.last().unwrap(), 12);
+    }
+
+    #[test]
+    fn entries_are_as_wide_as_the_largest_count_needs() {
+        let entries = |width: u32, height: u32| (width as usize + 1) * (height as usize + 1);
+        let small = GrayImage::from_pixel(255, 257, Luma([0]));
+        assert_eq!(occupancy_table_bytes(&small).len(), entries(255, 257) * 2);
+        let large = GrayImage::from_pixel(256, 256, Luma([0]));
+        assert_eq!(occupancy_table_bytes(&large).len(), entries(256, 256) * 4);
+        let last_entry = occupancy_table_bytes(&small)[entries(255, 257) * 2 - 2..].to_vec();
+        assert_eq!(last_entry, u16::MAX.to_le_bytes());
+    }
 }
@@ -1,17 +1,87 @@
 use crate::args::Args;
+use crate::common::{codegen_conditions, FlagCondition, Polygon, Rect};
 use crate::file::read_file;
+use crate::pass_map::codegen_bitmap_pass_map;
+use crate::tiled;
 use crate::ASSETS_DIR;
 use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2, TokenTree};
 use quote::{format_ident, quote};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
 
 #[derive(Deserialize)]
 struct Manifest {
-    background: String,
-    pass_map: String,
+    #[serde(default)]
+    layers: Vec<Layer>,
+    pass_map: Option<String>,
+    tiled: Option<TiledSource>,
+    #[serde(default)]
     initial_player_states: HashMap<String, PlayerState>,
     scripts: Scripts,
+    #[serde(default)]
+    npcs: Vec<Npc>,
+    #[serde(default)]
+    enemies: Vec<Enemy>,
+    #[serde(default)]
+    interaction_zones: Vec<InteractionZone>,
+    #[serde(default)]
+    triggers: Vec<Trigger>,
+    #[serde(default)]
+    encounter_zones: Vec<EncounterZone>,
+    #[serde(default)]
+    blocks: Vec<Block>,
+    #[serde(default)]
+    switches: Vec<Switch>,
+    #[serde(default)]
+    gates: Vec<Gate>,
+    #[serde(default)]
+    pickups: Vec<Pickup>,
+    #[serde(default)]
+    signs: Vec<Sign>,
+    #[serde(default)]
+    exits: Vec<Exit>,
+    corner_nudge: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct Layer {
+    texture: String,
+    #[serde(default)]
+    z: ZOrder,
+    #[serde(default = "default_parallax")]
+    parallax: f32,
+    #[serde(default = "default_opacity")]
+    opacity: f32,
+}
+
+#[derive(Deserialize, Copy, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+enum ZOrder {
+    #[default]
+    Below,
+    Above,
+}
+
+fn default_parallax() -> f32 {
+    1.0
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct TiledSource {
+    map: String,
+    #[serde(default = "default_collision_layer")]
+    collision_layer: String,
+}
+
+fn default_collision_layer() -> String {
+    String::from("collision")
 }
 
 #[derive(Deserialize)]
@@ -27,6 +97,262 @@ struct PlayerState {
 }
 
 #[derive(Deserialize)]
+struct Npc {
+    name: String,
+    x: f32,
+    y: f32,
+    direction: Direction,
+    textures: DirectionalTextures,
+    hitbox: Rect,
+    #[serde(default = "default_solid")]
+    solid: bool,
+    #[serde(default)]
+    layer: DrawLayer,
+    movement: Option<MovementProfile>,
+    interaction: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Enemy {
+    name: String,
+    x: f32,
+    y: f32,
+    direction: Direction,
+    textures: DirectionalTextures,
+    hitbox: Rect,
+    movement: Option<MovementProfile>,
+    #[serde(default)]
+    patrol: Vec<[f32; 2]>,
+    sight_range: f32,
+    encounter: String,
+}
+
+#[derive(Deserialize)]
+struct MovementProfile {
+    walk_speed: f32,
+    run_speed: Option<f32>,
+    acceleration: Option<f32>,
+    deceleration: Option<f32>,
+}
+
+fn codegen_optional_f32(value: Option<f32>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
+
+impl MovementProfile {
+    fn codegen(&self) -> TokenStream2 {
+        let walk_speed = self.walk_speed;
+        // Entities which can't run walk when asked to.
+        let run_speed = self.run_speed.unwrap_or(walk_speed);
+        let acceleration = codegen_optional_f32(self.acceleration);
+        let deceleration = codegen_optional_f32(self.deceleration);
+        quote! {
+            crate::overworld::movement::MovementProfile {
+                walk_speed: #walk_speed,
+                run_speed: #run_speed,
+                acceleration: #acceleration,
+                deceleration: #deceleration,
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Copy, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+enum DrawLayer {
+    Below,
+    #[default]
+    Sorted,
+    Above,
+}
+
+impl DrawLayer {
+    fn codegen(&self) -> TokenStream2 {
+        let variant = format_ident!(
+            "{}",
+            match self {
+                DrawLayer::Below => "Below",
+                DrawLayer::Sorted => "Sorted",
+                DrawLayer::Above => "Above",
+            }
+        );
+        quote! { crate::overworld::draw_order::DrawLayer::#variant }
+    }
+}
+
+fn default_solid() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct InteractionZone {
+    rect: Rect,
+    script: String,
+}
+
+#[derive(Deserialize)]
+struct Trigger {
+    /// Saves refer to the trigger by it, so it must stay the same when the manifest changes.
+    name: String,
+    rect: Option<Rect>,
+    polygon: Option<Vec<[f32; 2]>>,
+    on_enter: Option<TriggerAction>,
+    on_leave: Option<TriggerAction>,
+    on_stay: Option<TriggerAction>,
+    stay_seconds: Option<f32>,
+    #[serde(default)]
+    once: bool,
+    #[serde(default)]
+    conditions: Vec<FlagCondition>,
+}
+
+#[derive(Deserialize)]
+struct EncounterZone {
+    rect: Option<Rect>,
+    polygon: Option<Vec<[f32; 2]>>,
+    groups: Vec<EnemyGroup>,
+    distance: [f32; 2],
+    kill_counter: Option<String>,
+    #[serde(default)]
+    conditions: Vec<FlagCondition>,
+}
+
+#[derive(Deserialize)]
+struct EnemyGroup {
+    encounter: String,
+    #[serde(default = "default_weight")]
+    weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TriggerAction {
+    Event(String),
+    Script(String),
+}
+
+impl TriggerAction {
+    fn codegen(&self) -> TokenStream2 {
+        match self {
+            Self::Event(name) => quote! { crate::overworld::trigger::ActionParams::Event(#name) },
+            Self::Script(name) => quote! { crate::overworld::trigger::ActionParams::Script(#name) },
+        }
+    }
+}
+
+fn codegen_optional_action(action: &Option<TriggerAction>) -> TokenStream2 {
+    match action {
+        Some(action) => {
+            let action = action.codegen();
+            quote! { Some(#action) }
+        }
+        None => quote! { None },
+    }
+}
+
+#[derive(Deserialize)]
+struct Block {
+    name: String,
+    x: f32,
+    y: f32,
+    texture: String,
+    grid: f32,
+}
+
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum SwitchKind {
+    Floor,
+    Lever,
+}
+
+#[derive(Deserialize)]
+struct Switch {
+    kind: SwitchKind,
+    x: f32,
+    y: f32,
+    hitbox: Rect,
+    flag: String,
+    #[serde(default)]
+    sticky: bool,
+    textures: SwitchTextures,
+}
+
+#[derive(Deserialize)]
+struct SwitchTextures {
+    off: String,
+    on: String,
+}
+
+#[derive(Deserialize, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+enum GateKind {
+    Door,
+    Spikes,
+}
+
+#[derive(Deserialize)]
+struct Gate {
+    kind: GateKind,
+    x: f32,
+    y: f32,
+    hitbox: Rect,
+    textures: GateTextures,
+    open_when: Vec<FlagCondition>,
+    /// Only for spikes, which deal 1 damage unless told otherwise.
+    damage: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct Pickup {
+    item: String,
+    x: f32,
+    y: f32,
+    hitbox: Rect,
+    texture: String,
+    flag: String,
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Sign {
+    x: f32,
+    y: f32,
+    hitbox: Rect,
+    texture: Option<String>,
+    text: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct GateTextures {
+    closed: String,
+    open: String,
+    opening: Option<String>,
+    closing: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Exit {
+    rect: Rect,
+    room: String,
+}
+
+#[derive(Deserialize)]
+struct DirectionalTextures {
+    left: String,
+    right: String,
+    forward: String,
+    backward: String,
+}
+
+#[derive(Deserialize, Copy, Clone)]
 #[serde(rename_all = "lowercase")]
 enum Direction {
     Left,
@@ -35,15 +361,689 @@ enum Direction {
     Backward,
 }
 
-pub fn load_room(args: &Args) -> TokenStream {
-    let dir_full_path = format!("{}/rooms/{}", ASSETS_DIR, args.path);
-    let manifest_full_path = format!("{}/room.toml", dir_full_path);
+impl DirectionalTextures {
+    fn codegen(&self) -> TokenStream2 {
+        let DirectionalTextures {
+            left,
+            right,
+            forward,
+            backward,
+        } = self;
+        quote! {
+            crate::overworld::multiside::DirectionalTexturePaths {
+                left: ::std::string::String::from(#left),
+                right: ::std::string::String::from(#right),
+                forward: ::std::string::String::from(#forward),
+                backward: ::std::string::String::from(#backward),
+            }
+        }
+    }
+}
+
+impl Direction {
+    fn codegen(&self) -> TokenStream2 {
+        let variant = format_ident!(
+            "{}",
+            match self {
+                Direction::Backward => "Backward",
+                Direction::Forward => "Forward",
+                Direction::Left => "Left",
+                Direction::Right => "Right",
+            }
+        );
+        quote! { crate::overworld::move_trait::Direction::#variant }
+    }
+}
+
+fn codegen_optional_script(script: &Option<String>) -> TokenStream2 {
+    if let Some(script) = script {
+        quote! { Some(#script) }
+    } else {
+        quote! { None }
+    }
+}
+
+impl Npc {
+    fn codegen(&self) -> TokenStream2 {
+        let Npc { name, x, y, .. } = self;
+        let direction = self.direction.codegen();
+        let textures = self.textures.codegen();
+        let hitbox = self.hitbox.codegen();
+        let solid = self.solid;
+        let layer = self.layer.codegen();
+        let movement = match &self.movement {
+            Some(movement) => movement.codegen(),
+            None => quote! { ::std::default::Default::default() },
+        };
+        let interaction_script = codegen_optional_script(&self.interaction);
+        quote! {
+            crate::overworld::npc::CreationParams {
+                name: ::std::string::String::from(#name),
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                direction: #direction,
+                textures: #textures,
+                hitbox: #hitbox,
+                solid: #solid,
+                layer: #layer,
+                movement: #movement,
+                interaction_script: #interaction_script,
+            }
+        }
+    }
+}
+
+impl Enemy {
+    fn codegen(&self) -> TokenStream2 {
+        let Enemy {
+            name,
+            x,
+            y,
+            sight_range,
+            encounter,
+            ..
+        } = self;
+        let direction = self.direction.codegen();
+        let textures = self.textures.codegen();
+        let hitbox = self.hitbox.codegen();
+        let movement = match &self.movement {
+            Some(movement) => movement.codegen(),
+            None => quote! { ::std::default::Default::default() },
+        };
+        let patrol_xs = self.patrol.iter().map(|[x, _y]| x);
+        let patrol_ys = self.patrol.iter().map(|[_x, y]| y);
+        quote! {
+            crate::overworld::enemy::CreationParams {
+                name: ::std::string::String::from(#name),
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                direction: #direction,
+                textures: #textures,
+                hitbox: #hitbox,
+                movement: #movement,
+                patrol: vec![
+                    #(crate::geometry::OverworldPoint::<f32>::new(#patrol_xs, #patrol_ys)),*
+                ],
+                sight_range: #sight_range,
+                encounter: #encounter,
+            }
+        }
+    }
+}
 
-    let manifest: Manifest =
-        toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+impl Layer {
+    fn codegen(&self) -> TokenStream2 {
+        let Layer {
+            texture,
+            parallax,
+            opacity,
+            ..
+        } = self;
+        if !(0.0..=1.0).contains(opacity) {
+            panic!(
+                "Opacity of layer `{}` must be between 0 and 1, got {}",
+                texture, opacity
+            );
+        }
+        let z_order = format_ident!(
+            "{}",
+            match self.z {
+                ZOrder::Below => "BelowEntities",
+                ZOrder::Above => "AboveEntities",
+            }
+        );
+        quote! {
+            crate::overworld::layer::CreationParams {
+                texture_path: ::std::string::String::from(#texture),
+                z_order: crate::overworld::layer::ZOrder::#z_order,
+                parallax: #parallax,
+                opacity: #opacity,
+            }
+        }
+    }
+}
+
+impl InteractionZone {
+    fn codegen(&self) -> TokenStream2 {
+        let rect = self.rect.codegen();
+        let script = &self.script;
+        quote! {
+            crate::overworld::interaction::ZoneCreationParams {
+                rect: #rect,
+                script: #script,
+            }
+        }
+    }
+}
+
+fn codegen_shape(rect: &Option<Rect>, polygon: &Option<Vec<[f32; 2]>>, what: &str) -> TokenStream2 {
+    match (rect, polygon) {
+        (Some(rect), None) => {
+            let rect = rect.codegen();
+            quote! { crate::overworld::trigger::Shape::Rect(#rect) }
+        }
+        (None, Some(points)) => {
+            let polygon = Polygon {
+                points: points.clone(),
+            }
+            .codegen();
+            quote! { crate::overworld::trigger::Shape::Polygon(#polygon) }
+        }
+        _ => panic!("{} must have either a `rect` or a `polygon`", what),
+    }
+}
+
+impl Trigger {
+    fn codegen(&self) -> TokenStream2 {
+        let shape = codegen_shape(&self.rect, &self.polygon, "A trigger");
+        let on_enter = codegen_optional_action(&self.on_enter);
+        let on_leave = codegen_optional_action(&self.on_leave);
+        let on_stay = match (&self.on_stay, self.stay_seconds) {
+            (Some(action), Some(seconds)) if seconds > 0.0 => {
+                let action = action.codegen();
+                quote! {
+                    Some((::std::time::Duration::from_secs_f32(#seconds), #action))
+                }
+            }
+            (None, None) => quote! { None },
+            _ => panic!("`on_stay` of a trigger needs a positive `stay_seconds`, and vice versa"),
+        };
+        let name = &self.name;
+        let once = self.once;
+        let conditions = codegen_conditions(&self.conditions);
+        quote! {
+            crate::overworld::trigger::CreationParams {
+                name: #name,
+                shape: #shape,
+                on_enter: #on_enter,
+                on_leave: #on_leave,
+                on_stay: #on_stay,
+                once: #once,
+                conditions: #conditions,
+            }
+        }
+    }
+}
+
+impl EncounterZone {
+    fn codegen(&self) -> TokenStream2 {
+        let shape = codegen_shape(&self.rect, &self.polygon, "An encounter zone");
+        let [min_distance, max_distance] = self.distance;
+        if min_distance <= 0.0 || max_distance < min_distance {
+            panic!(
+                "The `distance` of an encounter zone must be a positive range, got {:?}",
+                self.distance
+            );
+        }
+        if self.groups.iter().all(|group| group.weight == 0) {
+            panic!("An encounter zone needs an enemy group with a positive weight");
+        }
+        let group_encounters = self.groups.iter().map(|group| &group.encounter);
+        let group_weights = self.groups.iter().map(|group| group.weight);
+        let kill_counter = match &self.kill_counter {
+            Some(flag) => quote! { Some(#flag) },
+            None => quote! { None },
+        };
+        let conditions = codegen_conditions(&self.conditions);
+        quote! {
+            crate::overworld::encounter::CreationParams {
+                shape: #shape,
+                table: crate::overworld::encounter::EncounterTable {
+                    groups: vec![
+                        #(
+                            crate::overworld::encounter::EnemyGroup {
+                                encounter: #group_encounters,
+                                weight: #group_weights,
+                            }
+                        ),*
+                    ],
+                    distance: (#min_distance, #max_distance),
+                    conditions: #conditions,
+                    kill_counter: #kill_counter,
+                },
+            }
+        }
+    }
+}
+
+impl Block {
+    fn codegen(&self) -> TokenStream2 {
+        let Block {
+            name,
+            x,
+            y,
+            texture,
+            grid,
+        } = self;
+        if *grid <= 0.0 {
+            panic!("The grid of block `{}` must be positive", name);
+        }
+        quote! {
+            crate::overworld::puzzle::BlockCreationParams {
+                name: ::std::string::String::from(#name),
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                texture: ::std::string::String::from(#texture),
+                grid: #grid,
+            }
+        }
+    }
+}
+
+impl Switch {
+    fn codegen(&self) -> TokenStream2 {
+        let Switch {
+            x,
+            y,
+            flag,
+            sticky,
+            textures,
+            ..
+        } = self;
+        let kind = match self.kind {
+            SwitchKind::Floor => {
+                quote! { crate::overworld::puzzle::SwitchKind::Floor { sticky: #sticky } }
+            }
+            SwitchKind::Lever if *sticky => panic!("Lever `{}` can't be sticky", flag),
+            SwitchKind::Lever => quote! { crate::overworld::puzzle::SwitchKind::Lever },
+        };
+        let hitbox = self.hitbox.codegen();
+        let SwitchTextures { off, on } = textures;
+        quote! {
+            crate::overworld::puzzle::SwitchCreationParams {
+                kind: #kind,
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                hitbox: #hitbox,
+                flag: #flag,
+                off_texture: ::std::string::String::from(#off),
+                on_texture: ::std::string::String::from(#on),
+            }
+        }
+    }
+}
+
+impl Gate {
+    fn codegen(&self) -> TokenStream2 {
+        let Gate { x, y, textures, .. } = self;
+        let kind = match (self.kind, self.damage) {
+            (GateKind::Door, None) => quote! { Door },
+            (GateKind::Door, Some(_)) => panic!("Doors don't deal damage, only spikes do"),
+            (GateKind::Spikes, damage) => {
+                let damage = damage.unwrap_or(1);
+                quote! { Spikes { damage: #damage } }
+            }
+        };
+        let hitbox = self.hitbox.codegen();
+        let open_when = codegen_conditions(&self.open_when);
+        let GateTextures {
+            closed,
+            open,
+            opening,
+            closing,
+        } = textures;
+        let opening = codegen_optional_string(opening);
+        let closing = codegen_optional_string(closing);
+        quote! {
+            crate::overworld::puzzle::GateCreationParams {
+                kind: crate::overworld::puzzle::GateKind::#kind,
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                hitbox: #hitbox,
+                textures: crate::overworld::puzzle::GateTexturePaths {
+                    closed: ::std::string::String::from(#closed),
+                    open: ::std::string::String::from(#open),
+                    opening: #opening,
+                    closing: #closing,
+                },
+                open_when: #open_when,
+            }
+        }
+    }
+}
+
+impl Pickup {
+    fn codegen(&self) -> TokenStream2 {
+        let Pickup {
+            item,
+            x,
+            y,
+            texture,
+            flag,
+            ..
+        } = self;
+        let hitbox = self.hitbox.codegen();
+        let text = match &self.text {
+            Some(text_id) => quote! { Some(#text_id) },
+            None => quote! { None },
+        };
+        quote! {
+            crate::overworld::pickup::PickupCreationParams {
+                item: #item,
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                hitbox: #hitbox,
+                texture: ::std::string::String::from(#texture),
+                flag: #flag,
+                text: #text,
+            }
+        }
+    }
+}
+
+impl Sign {
+    fn codegen(&self) -> TokenStream2 {
+        let Sign { x, y, text, .. } = self;
+        if text.is_empty() {
+            panic!("The sign at ({}, {}) must have some text", x, y);
+        }
+        let hitbox = self.hitbox.codegen();
+        let texture = codegen_optional_string(&self.texture);
+        quote! {
+            crate::overworld::sign::SignCreationParams {
+                position: crate::geometry::OverworldPoint::<f32>::from([#x, #y]),
+                hitbox: #hitbox,
+                texture: #texture,
+                text: vec![#(#text),*],
+            }
+        }
+    }
+}
+
+fn codegen_optional_string(value: &Option<String>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { Some(::std::string::String::from(#value)) },
+        None => quote! { None },
+    }
+}
+
+impl Exit {
+    fn codegen(&self) -> TokenStream2 {
+        let rect = self.rect.codegen();
+        let room = &self.room;
+        quote! {
+            crate::overworld::exit::Exit {
+                rect: #rect,
+                target_room: ::std::string::String::from(#room),
+            }
+        }
+    }
+}
 
-    let background_path = manifest.background;
-    let pass_map_path = manifest.pass_map;
+fn tiled_texture_name(room_name: &str, layer_name: &str) -> String {
+    format!("tiled:{}/{}", room_name, layer_name)
+}
+
+fn tiled_pass_map_name(room_name: &str) -> String {
+    format!("tiled:{}", room_name)
+}
+
+fn tiled_property<T: DeserializeOwned>(
+    properties: &tiled::Properties,
+    name: &str,
+    owner: &str,
+) -> Option<T> {
+    properties.get(name).map(|value| {
+        value.clone().try_into().unwrap_or_else(|e| {
+            panic!("Invalid value of property `{}` of `{}`: {}", name, owner, e)
+        })
+    })
+}
+
+fn required_tiled_property<T: DeserializeOwned>(
+    properties: &tiled::Properties,
+    name: &str,
+    owner: &str,
+) -> T {
+    tiled_property(properties, name, owner)
+        .unwrap_or_else(|| panic!("`{}` lacks the `{}` property", owner, name))
+}
+
+// Tile layers which become room layers: everything visible except the collision layer.
+fn rendered_tile_layers<'a>(
+    map: &'a tiled::Map,
+    source: &'a TiledSource,
+) -> impl Iterator<Item = &'a tiled::TileLayer> {
+    map.layers.iter().filter_map(move |layer| match layer {
+        tiled::Layer::Tiles(tile_layer)
+            if tile_layer.visible && tile_layer.name != source.collision_layer =>
+        {
+            Some(tile_layer)
+        }
+        _ => None,
+    })
+}
+
+impl Rect {
+    fn from_tiled(object: &tiled::Object) -> Self {
+        Rect {
+            left: object.x,
+            top: object.y,
+            right: object.x + object.width,
+            bottom: object.y + object.height,
+        }
+    }
+}
+
+impl Manifest {
+    fn read(room_name: &str) -> (String, Manifest) {
+        let dir_full_path = format!("{}/rooms/{}", ASSETS_DIR, room_name);
+        let manifest_full_path = format!("{}/room.toml", dir_full_path);
+        let manifest =
+            toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+        (dir_full_path, manifest)
+    }
+
+    // Tiled layers go below the ones listed in the manifest, and objects are added to the ones
+    // listed in the manifest.
+    fn import_tiled(&mut self, room_name: &str, map: &tiled::Map, source: &TiledSource) {
+        if self.pass_map.is_some() {
+            panic!("A room with a Tiled map takes its pass map from the map's collision layer");
+        }
+        if map.tile_layer(&source.collision_layer).is_none() {
+            panic!("Tiled map has no `{}` layer", source.collision_layer);
+        }
+        self.pass_map = Some(tiled_pass_map_name(room_name));
+
+        let mut layers: Vec<_> = rendered_tile_layers(map, source)
+            .map(|tile_layer| Layer {
+                texture: tiled_texture_name(room_name, &tile_layer.name),
+                z: tiled_property(&tile_layer.properties, "z", &tile_layer.name)
+                    .unwrap_or_default(),
+                parallax: tile_layer.parallax,
+                opacity: tile_layer.opacity,
+            })
+            .collect();
+        layers.append(&mut self.layers);
+        self.layers = layers;
+
+        for layer in &map.layers {
+            if let tiled::Layer::Objects(object_layer) = layer {
+                for object in &object_layer.objects {
+                    self.import_tiled_object(object);
+                }
+            }
+        }
+    }
+
+    fn import_tiled_object(&mut self, object: &tiled::Object) {
+        let owner = format!("{} object `{}`", object.kind, object.name);
+        let properties = &object.properties;
+        let (x, y) = object.center();
+        match object.kind.as_str() {
+            "spawn" => {
+                let from =
+                    tiled_property(properties, "from", &owner).unwrap_or_else(|| String::from("_"));
+                let state = PlayerState {
+                    x,
+                    y,
+                    direction: required_tiled_property(properties, "direction", &owner),
+                };
+                if self.initial_player_states.insert(from, state).is_some() {
+                    panic!("Duplicate spawn point: {}", owner);
+                }
+            }
+            "exit" => self.exits.push(Exit {
+                rect: Rect::from_tiled(object),
+                room: required_tiled_property(properties, "room", &owner),
+            }),
+            "interaction" => self.interaction_zones.push(InteractionZone {
+                rect: Rect::from_tiled(object),
+                script: required_tiled_property(properties, "script", &owner),
+            }),
+            "npc" => {
+                let textures: String = required_tiled_property(properties, "textures", &owner);
+                let (half_width, half_height) = (object.width / 2.0, object.height / 2.0);
+                self.npcs.push(Npc {
+                    name: object.name.clone(),
+                    x,
+                    y,
+                    direction: required_tiled_property(properties, "direction", &owner),
+                    textures: DirectionalTextures {
+                        left: format!("{}/left", textures),
+                        right: format!("{}/right", textures),
+                        forward: format!("{}/forward", textures),
+                        backward: format!("{}/backward", textures),
+                    },
+                    hitbox: Rect {
+                        left: -half_width,
+                        top: -half_height,
+                        right: half_width,
+                        bottom: half_height,
+                    },
+                    solid: tiled_property(properties, "solid", &owner).unwrap_or(true),
+                    layer: tiled_property(properties, "layer", &owner).unwrap_or_default(),
+                    movement: tiled_property(properties, "walk_speed", &owner).map(|walk_speed| {
+                        MovementProfile {
+                            walk_speed,
+                            run_speed: tiled_property(properties, "run_speed", &owner),
+                            acceleration: tiled_property(properties, "acceleration", &owner),
+                            deceleration: tiled_property(properties, "deceleration", &owner),
+                        }
+                    }),
+                    interaction: tiled_property(properties, "interaction", &owner),
+                });
+            }
+            "trigger" => {
+                if object.name.is_empty() {
+                    panic!("A trigger needs a name: {}", owner);
+                }
+                // Each of `enter`, `leave` and `stay` takes either an event or a script.
+                let action = |when: &str| {
+                    let event = tiled_property(properties, &format!("on_{}_event", when), &owner);
+                    let script = tiled_property(properties, &format!("on_{}_script", when), &owner);
+                    match (event, script) {
+                        (Some(_), Some(_)) => {
+                            panic!("`{}` has both an event and a script on {}", owner, when)
+                        }
+                        (Some(event), None) => Some(TriggerAction::Event(event)),
+                        (None, Some(script)) => Some(TriggerAction::Script(script)),
+                        (None, None) => None,
+                    }
+                };
+                let (rect, polygon) = match &object.polygon {
+                    Some(points) => {
+                        let points = points
+                            .iter()
+                            .map(|[point_x, point_y]| [object.x + point_x, object.y + point_y])
+                            .collect();
+                        (None, Some(points))
+                    }
+                    None => (Some(Rect::from_tiled(object)), None),
+                };
+                // A single condition fits in the object's properties.
+                let condition =
+                    tiled_property(properties, "flag", &owner).map(|flag| FlagCondition {
+                        flag,
+                        unset: tiled_property(properties, "unset", &owner).unwrap_or(false),
+                        at_least: tiled_property(properties, "at_least", &owner),
+                        below: tiled_property(properties, "below", &owner),
+                    });
+                self.triggers.push(Trigger {
+                    name: object.name.clone(),
+                    rect,
+                    polygon,
+                    on_enter: action("enter"),
+                    on_leave: action("leave"),
+                    on_stay: action("stay"),
+                    stay_seconds: tiled_property(properties, "stay_seconds", &owner),
+                    once: tiled_property(properties, "once", &owner).unwrap_or(false),
+                    conditions: condition.into_iter().collect(),
+                });
+            }
+            _ => panic!("Unknown Tiled object type: {}", owner),
+        }
+    }
+}
+
+fn codegen_rgba_texture(image: &image::RgbaImage) -> TokenStream2 {
+    let width = u16::try_from(image.width()).expect("Texture is too wide");
+    let height = u16::try_from(image.height()).expect("Texture is too tall");
+    let image_bytes = TokenTree::Literal(Literal::byte_string(image as &[u8]));
+    quote! {
+        crate::graphics::texture::Texture::new_static(
+            {
+                let mut image = ::ggez::graphics::Image::from_rgba8(
+                    ctx,
+                    #width,
+                    #height,
+                    #image_bytes,
+                ).expect("Failed to create image");
+                image.set_filter(::ggez::graphics::FilterMode::Nearest);
+                image
+            },
+            1.0,
+        )
+    }
+}
+
+/// Generates the textures and the pass map of a room made in Tiled, named the way `load_room`
+/// refers to them.
+pub fn load_tiled_room_assets(args: &Args) -> TokenStream {
+    let (dir_full_path, manifest) = Manifest::read(&args.path);
+    let source = manifest
+        .tiled
+        .unwrap_or_else(|| panic!("Room `{}` is not made in Tiled", args.path));
+    let map = tiled::load_map(&format!("{}/{}", dir_full_path, source.map));
+
+    let texture_names: Vec<_> = rendered_tile_layers(&map, &source)
+        .map(|tile_layer| tiled_texture_name(&args.path, &tile_layer.name))
+        .collect();
+    let textures: Vec<_> = rendered_tile_layers(&map, &source)
+        .map(|tile_layer| codegen_rgba_texture(&map.render_tile_layer(tile_layer)))
+        .collect();
+
+    let collision_layer = map
+        .tile_layer(&source.collision_layer)
+        .unwrap_or_else(|| panic!("Tiled map has no `{}` layer", source.collision_layer));
+    let pass_map_name = tiled_pass_map_name(&args.path);
+    let pass_map = codegen_bitmap_pass_map(
+        &map.collision_bitmap(collision_layer),
+        1.0,
+        &BTreeMap::new(),
+    );
+
+    (quote! {
+        (
+            vec![
+                #(
+                    (::std::string::String::from(#texture_names), #textures)
+                ),*
+            ],
+            (::std::string::String::from(#pass_map_name), #pass_map),
+        )
+    })
+    .into()
+}
+
+pub fn load_room(args: &Args) -> TokenStream {
+    let (dir_full_path, mut manifest) = Manifest::read(&args.path);
+    if let Some(source) = manifest.tiled.take() {
+        let map = tiled::load_map(&format!("{}/{}", dir_full_path, source.map));
+        manifest.import_tiled(&args.path, &map, &source);
+    }
+
+    let layers: Vec<_> = manifest.layers.iter().map(Layer::codegen).collect();
+    let pass_map_path = manifest.pass_map.unwrap_or_else(|| {
+        panic!(
+            "Room `{}` has neither a pass map nor a Tiled map",
+            args.path
+        )
+    });
     let initial_player_states_keys: Vec<_> = manifest.initial_player_states.keys().collect();
     let initial_player_states_values_x: Vec<_> = manifest
         .initial_player_states
@@ -58,28 +1058,47 @@ pub fn load_room(args: &Args) -> TokenStream {
     let initial_player_states_values_dir: Vec<_> = manifest
         .initial_player_states
         .values()
-        .map(|s| {
-            format_ident!(
-                "{}",
-                match s.direction {
-                    Direction::Backward => "Backward",
-                    Direction::Forward => "Forward",
-                    Direction::Left => "Left",
-                    Direction::Right => "Right",
-                }
-            )
-        })
+        .map(|s| s.direction.codegen())
         .collect();
 
-    let init_script = if let Some(script) = manifest.scripts.init {
-        quote! { Some(#script) }
-    } else {
-        quote! { None }
+    let init_script = codegen_optional_script(&manifest.scripts.init);
+    let npcs: Vec<_> = manifest.npcs.iter().map(Npc::codegen).collect();
+    let enemies: Vec<_> = manifest.enemies.iter().map(Enemy::codegen).collect();
+    let interaction_zones: Vec<_> = manifest
+        .interaction_zones
+        .iter()
+        .map(InteractionZone::codegen)
+        .collect();
+    let corner_nudge = match manifest.corner_nudge {
+        Some(max_distance) => quote! {
+            crate::overworld::collide::CornerNudge { max_distance: #max_distance }
+        },
+        None => quote! { ::std::default::Default::default() },
     };
+    let mut trigger_names = BTreeSet::new();
+    for trigger in &manifest.triggers {
+        if !trigger_names.insert(&trigger.name) {
+            panic!("Duplicate trigger name: {}", trigger.name);
+        }
+    }
+    let triggers: Vec<_> = manifest.triggers.iter().map(Trigger::codegen).collect();
+    let encounter_zones: Vec<_> = manifest
+        .encounter_zones
+        .iter()
+        .map(EncounterZone::codegen)
+        .collect();
+    let blocks: Vec<_> = manifest.blocks.iter().map(Block::codegen).collect();
+    let switches: Vec<_> = manifest.switches.iter().map(Switch::codegen).collect();
+    let gates: Vec<_> = manifest.gates.iter().map(Gate::codegen).collect();
+    let pickups: Vec<_> = manifest.pickups.iter().map(Pickup::codegen).collect();
+    let signs: Vec<_> = manifest.signs.iter().map(Sign::codegen).collect();
+    let exits: Vec<_> = manifest.exits.iter().map(Exit::codegen).collect();
+    let name = &args.path;
 
     (quote! {
         crate::overworld::room::PartialCreationParams {
-            background_path: ::std::string::String::from(#background_path),
+            name: ::std::string::String::from(#name),
+            layers: vec![#(#layers),*],
             pass_map_path: ::std::string::String::from(#pass_map_path),
             initial_player_states: vec![
                 #(
@@ -90,12 +1109,24 @@ pub fn load_room(args: &Args) -> TokenStream {
                                 #initial_player_states_values_x,
                                 #initial_player_states_values_y,
                             ]),
-                            crate::overworld::move_trait::Direction::#initial_player_states_values_dir,
+                            #initial_player_states_values_dir,
                         )
                     )
                 ),*
             ].into_iter().collect(),
             init_script: #init_script,
+            npcs: vec![#(#npcs),*],
+            enemies: vec![#(#enemies),*],
+            interaction_zones: vec![#(#interaction_zones),*],
+            triggers: vec![#(#triggers),*],
+            encounter_zones: vec![#(#encounter_zones),*],
+            blocks: vec![#(#blocks),*],
+            switches: vec![#(#switches),*],
+            gates: vec![#(#gates),*],
+            pickups: vec![#(#pickups),*],
+            signs: vec![#(#signs),*],
+            exits: vec![#(#exits),*],
+            corner_nudge: #corner_nudge,
         }
     })
     .into()
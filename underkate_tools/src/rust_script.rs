@@ -12,7 +12,7 @@ struct Manifest {
 }
 
 pub fn load_rust_script(args: &Args) -> TokenStream {
-    let dir_full_path = format!("{}/{}", ASSETS_DIR, args.path);
+    let dir_full_path = format!("{}/scripts/{}", ASSETS_DIR, args.path);
     let manifest_full_path = format!("{}/script.toml", dir_full_path);
 
     let manifest: Manifest =
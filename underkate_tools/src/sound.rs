@@ -0,0 +1,38 @@
+use crate::args::Args;
+use crate::file::read_file;
+use crate::ASSETS_DIR;
+use proc_macro::TokenStream;
+use quote::quote;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(rename = "file")]
+    file_path: String,
+    #[serde(default = "default_volume")]
+    volume: f32,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+pub fn load_sound(args: &Args) -> TokenStream {
+    let dir_full_path = format!("{}/sounds/{}", ASSETS_DIR, args.path);
+    let manifest_full_path = format!("{}/sound.toml", dir_full_path);
+
+    let manifest: Manifest =
+        toml::from_str(&read_file(&manifest_full_path)).expect("Failed to parse manifest file");
+    let file_path = manifest.file_path;
+    let volume = manifest.volume;
+
+    (quote! {
+        crate::audio::Sound::new(
+            ::ggez::audio::SoundData::from_bytes(
+                include_bytes!(concat!(#dir_full_path, "/", #file_path)),
+            ),
+            #volume,
+        )
+    })
+    .into()
+}
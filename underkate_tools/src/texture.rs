@@ -23,7 +23,7 @@ enum Manifest {
 }
 
 pub fn load_texture(args: &Args) -> TokenStream {
-    let dir_full_path = format!("{}/{}", ASSETS_DIR, args.path);
+    let dir_full_path = format!("{}/textures/{}", ASSETS_DIR, args.path);
     let manifest_full_path = format!("{}/texture.toml", dir_full_path);
 
     let manifest: Manifest =
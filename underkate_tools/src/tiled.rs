@@ -0,0 +1,214 @@
+mod tmj;
+mod tmx;
+
+use image::{imageops, GrayImage, Luma, RgbaImage};
+use std::collections::HashMap;
+use std::path::Path;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const GID_MASK: u32 = !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY);
+
+/// Custom properties, typed the way they are typed in Tiled.
+pub type Properties = HashMap<String, toml::Value>;
+
+pub struct Map {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<Tileset>,
+    pub layers: Vec<Layer>,
+}
+
+pub struct Tileset {
+    pub first_gid: u32,
+    pub image_path: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    pub tile_count: u32,
+}
+
+pub enum Layer {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+}
+
+pub struct TileLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    pub parallax: f32,
+    pub properties: Properties,
+    pub data: Vec<u32>,
+}
+
+pub struct ObjectLayer {
+    pub objects: Vec<Object>,
+}
+
+pub struct Object {
+    pub name: String,
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// The vertices of a polygon object, relative to its position.
+    pub polygon: Option<Vec<[f32; 2]>>,
+    pub properties: Properties,
+}
+
+impl Object {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+fn resolve_path(relative_to_file: &str, path: &str) -> String {
+    Path::new(relative_to_file)
+        .parent()
+        .expect("Invalid file path")
+        .join(path)
+        .to_str()
+        .expect("Path is not valid UTF-8")
+        .to_owned()
+}
+
+fn parallax(layer_name: &str, parallax_x: f32, parallax_y: f32) -> f32 {
+    if parallax_x != parallax_y {
+        panic!(
+            "Layer `{}` has different horizontal and vertical parallax factors, which is not supported",
+            layer_name
+        );
+    }
+    parallax_x
+}
+
+fn unsupported_encoding(layer_name: &str) -> ! {
+    panic!(
+        "Layer `{}` is not stored as CSV. Set the tile layer format to CSV in the map properties",
+        layer_name
+    )
+}
+
+/// Loads a `.tmx` or `.tmj` map, along with external tilesets it refers to.
+pub fn load_map(full_path: &str) -> Map {
+    let map = match Path::new(full_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+    {
+        Some("tmx") => tmx::load_map(full_path),
+        Some("tmj") | Some("json") => tmj::load_map(full_path),
+        _ => panic!("Unknown Tiled map format: `{}`", full_path),
+    };
+    for layer in &map.layers {
+        if let Layer::Tiles(tile_layer) = layer {
+            if tile_layer.data.len() != (map.width * map.height) as usize {
+                panic!("Layer `{}` does not cover the whole map", tile_layer.name);
+            }
+        }
+    }
+    map
+}
+
+impl Map {
+    pub fn pixel_width(&self) -> u32 {
+        self.width * self.tile_width
+    }
+
+    pub fn pixel_height(&self) -> u32 {
+        self.height * self.tile_height
+    }
+
+    pub fn tile_layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Tiles(tile_layer) if tile_layer.name == name => Some(tile_layer),
+            _ => None,
+        })
+    }
+
+    fn tileset_for(&self, gid: u32) -> &Tileset {
+        self.tilesets
+            .iter()
+            .filter(|tileset| tileset.first_gid <= gid)
+            .max_by_key(|tileset| tileset.first_gid)
+            .filter(|tileset| gid - tileset.first_gid < tileset.tile_count)
+            .unwrap_or_else(|| panic!("No tileset contains tile {}", gid))
+    }
+
+    /// Draws the layer into an image the size of the whole map. Tiles larger than the map grid
+    /// are aligned to the bottom-left corner of their cell, as Tiled does.
+    pub fn render_tile_layer(&self, layer: &TileLayer) -> RgbaImage {
+        let tileset_images: Vec<RgbaImage> = self
+            .tilesets
+            .iter()
+            .map(|tileset| {
+                image::open(&tileset.image_path)
+                    .unwrap_or_else(|e| panic!("Failed to open `{}`: {}", tileset.image_path, e))
+                    .into_rgba8()
+            })
+            .collect();
+
+        let mut target = RgbaImage::new(self.pixel_width(), self.pixel_height());
+        for (index, &raw_gid) in layer.data.iter().enumerate() {
+            let gid = raw_gid & GID_MASK;
+            if gid == 0 {
+                continue;
+            }
+            let tileset = self.tileset_for(gid);
+            let tileset_index = self
+                .tilesets
+                .iter()
+                .position(|other| std::ptr::eq(other, tileset))
+                .unwrap();
+            let local_id = gid - tileset.first_gid;
+            let source_x = tileset.margin
+                + (local_id % tileset.columns) * (tileset.tile_width + tileset.spacing);
+            let source_y = tileset.margin
+                + (local_id / tileset.columns) * (tileset.tile_height + tileset.spacing);
+            let mut tile = imageops::crop_imm(
+                &tileset_images[tileset_index],
+                source_x,
+                source_y,
+                tileset.tile_width,
+                tileset.tile_height,
+            )
+            .to_image();
+            if raw_gid & FLIPPED_DIAGONALLY != 0 {
+                tile = imageops::flip_horizontal(&imageops::rotate90(&tile));
+            }
+            if raw_gid & FLIPPED_HORIZONTALLY != 0 {
+                tile = imageops::flip_horizontal(&tile);
+            }
+            if raw_gid & FLIPPED_VERTICALLY != 0 {
+                tile = imageops::flip_vertical(&tile);
+            }
+
+            let column = index as u32 % self.width;
+            let row = index as u32 / self.width;
+            let x = column * self.tile_width;
+            let y = ((row + 1) * self.tile_height).saturating_sub(tile.height());
+            imageops::overlay(&mut target, &tile, x, y);
+        }
+        target
+    }
+
+    /// Every cell with a tile in the layer is impassable; empty cells are passable.
+    pub fn collision_bitmap(&self, layer: &TileLayer) -> GrayImage {
+        GrayImage::from_fn(self.pixel_width(), self.pixel_height(), |x, y| {
+            let column = x / self.tile_width;
+            let row = y / self.tile_height;
+            let gid = layer.data[(row * self.width + column) as usize] & GID_MASK;
+            if gid == 0 {
+                Luma([255])
+            } else {
+                Luma([0])
+            }
+        })
+    }
+}
@@ -0,0 +1,232 @@
+use super::{
+    parallax, resolve_path, unsupported_encoding, Layer, Map, Object, ObjectLayer, Properties,
+    TileLayer, Tileset,
+};
+use crate::file::read_file;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct MapFile {
+    width: u32,
+    height: u32,
+    #[serde(rename = "tilewidth")]
+    tile_width: u32,
+    #[serde(rename = "tileheight")]
+    tile_height: u32,
+    #[serde(default)]
+    infinite: bool,
+    orientation: String,
+    tilesets: Vec<TilesetRef>,
+    layers: Vec<LayerFile>,
+}
+
+#[derive(Deserialize)]
+struct TilesetRef {
+    #[serde(rename = "firstgid")]
+    first_gid: u32,
+    source: Option<String>,
+    #[serde(flatten)]
+    embedded: Option<TilesetFile>,
+}
+
+#[derive(Deserialize)]
+struct TilesetFile {
+    image: String,
+    #[serde(rename = "tilewidth")]
+    tile_width: u32,
+    #[serde(rename = "tileheight")]
+    tile_height: u32,
+    columns: u32,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(rename = "tilecount")]
+    tile_count: u32,
+}
+
+#[derive(Deserialize)]
+struct PropertyFile {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "lowercase")]
+enum LayerFile {
+    TileLayer {
+        name: String,
+        #[serde(default = "default_true")]
+        visible: bool,
+        #[serde(default = "default_one")]
+        opacity: f32,
+        #[serde(rename = "parallaxx", default = "default_one")]
+        parallax_x: f32,
+        #[serde(rename = "parallaxy", default = "default_one")]
+        parallax_y: f32,
+        #[serde(default)]
+        properties: Vec<PropertyFile>,
+        data: serde_json::Value,
+    },
+    ObjectGroup {
+        objects: Vec<ObjectFile>,
+    },
+    ImageLayer {
+        name: String,
+    },
+    Group {
+        name: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct ObjectFile {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type", alias = "class", default)]
+    kind: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    polygon: Option<Vec<PointFile>>,
+    #[serde(default)]
+    properties: Vec<PropertyFile>,
+}
+
+#[derive(Deserialize)]
+struct PointFile {
+    x: f32,
+    y: f32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn convert_properties(properties: Vec<PropertyFile>) -> Properties {
+    properties
+        .into_iter()
+        .map(|property| {
+            let value = match property.value {
+                serde_json::Value::Bool(value) => toml::Value::Boolean(value),
+                serde_json::Value::Number(number) => match number.as_i64() {
+                    Some(value) => toml::Value::Integer(value),
+                    None => toml::Value::Float(number.as_f64().unwrap()),
+                },
+                serde_json::Value::String(value) => toml::Value::String(value),
+                other => panic!(
+                    "Unsupported value of property `{}`: {}",
+                    property.name, other
+                ),
+            };
+            (property.name, value)
+        })
+        .collect()
+}
+
+fn load_tileset(map_path: &str, tileset_ref: TilesetRef) -> Tileset {
+    let (tileset_path, tileset) = match (tileset_ref.source, tileset_ref.embedded) {
+        (Some(source), _) => {
+            let tileset_path = resolve_path(map_path, &source);
+            let tileset: TilesetFile = serde_json::from_str(&read_file(&tileset_path))
+                .expect("Failed to parse tileset file");
+            (tileset_path, tileset)
+        }
+        (None, Some(tileset)) => (String::from(map_path), tileset),
+        (None, None) => panic!("Tileset has neither a source nor an image"),
+    };
+    Tileset {
+        first_gid: tileset_ref.first_gid,
+        image_path: resolve_path(&tileset_path, &tileset.image),
+        tile_width: tileset.tile_width,
+        tile_height: tileset.tile_height,
+        columns: tileset.columns,
+        margin: tileset.margin,
+        spacing: tileset.spacing,
+        tile_count: tileset.tile_count,
+    }
+}
+
+fn convert_layer(layer: LayerFile) -> Layer {
+    match layer {
+        LayerFile::TileLayer {
+            name,
+            visible,
+            opacity,
+            parallax_x,
+            parallax_y,
+            properties,
+            data,
+        } => {
+            let data = match data {
+                serde_json::Value::Array(cells) => cells
+                    .iter()
+                    .map(|cell| cell.as_u64().expect("Invalid tile") as u32)
+                    .collect(),
+                _ => unsupported_encoding(&name),
+            };
+            Layer::Tiles(TileLayer {
+                parallax: parallax(&name, parallax_x, parallax_y),
+                name,
+                visible,
+                opacity,
+                properties: convert_properties(properties),
+                data,
+            })
+        }
+        LayerFile::ObjectGroup { objects } => Layer::Objects(ObjectLayer {
+            objects: objects
+                .into_iter()
+                .map(|object| Object {
+                    name: object.name,
+                    kind: object.kind,
+                    x: object.x,
+                    y: object.y,
+                    width: object.width,
+                    height: object.height,
+                    polygon: object
+                        .polygon
+                        .map(|points| points.iter().map(|point| [point.x, point.y]).collect()),
+                    properties: convert_properties(object.properties),
+                })
+                .collect(),
+        }),
+        LayerFile::ImageLayer { name } | LayerFile::Group { name } => {
+            panic!(
+                "Layer `{}` is an image layer or a group, which are not supported",
+                name
+            )
+        }
+    }
+}
+
+pub fn load_map(full_path: &str) -> Map {
+    let map: MapFile =
+        serde_json::from_str(&read_file(full_path)).expect("Failed to parse Tiled map file");
+    if map.infinite {
+        panic!("Infinite Tiled maps are not supported");
+    }
+    if map.orientation != "orthogonal" {
+        panic!("Only orthogonal Tiled maps are supported");
+    }
+    Map {
+        width: map.width,
+        height: map.height,
+        tile_width: map.tile_width,
+        tile_height: map.tile_height,
+        tilesets: map
+            .tilesets
+            .into_iter()
+            .map(|tileset_ref| load_tileset(full_path, tileset_ref))
+            .collect(),
+        layers: map.layers.into_iter().map(convert_layer).collect(),
+    }
+}
@@ -0,0 +1,188 @@
+use super::{
+    parallax, resolve_path, unsupported_encoding, Layer, Map, Object, ObjectLayer, Properties,
+    TileLayer, Tileset,
+};
+use crate::file::read_file;
+use roxmltree::{Document, Node};
+use std::str::FromStr;
+
+fn attribute<T: FromStr>(node: Node, name: &str) -> Option<T> {
+    node.attribute(name).map(|value| {
+        value.parse().unwrap_or_else(|_| {
+            panic!(
+                "Invalid value of attribute `{}` of <{}>: `{}`",
+                name,
+                node.tag_name().name(),
+                value
+            )
+        })
+    })
+}
+
+fn required_attribute<T: FromStr>(node: Node, name: &str) -> T {
+    attribute(node, name).unwrap_or_else(|| {
+        panic!(
+            "<{}> lacks the `{}` attribute",
+            node.tag_name().name(),
+            name
+        )
+    })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn parse_properties(node: Node) -> Properties {
+    let properties = match child(node, "properties") {
+        Some(properties) => properties,
+        None => return Properties::new(),
+    };
+    properties
+        .children()
+        .filter(|property| property.has_tag_name("property"))
+        .map(|property| {
+            let name: String = required_attribute(property, "name");
+            // Multiline strings are stored as text instead of an attribute.
+            let text = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or("");
+            let value = match property.attribute("type").unwrap_or("string") {
+                "bool" => toml::Value::Boolean(required_attribute(property, "value")),
+                "int" | "object" => toml::Value::Integer(required_attribute(property, "value")),
+                "float" => toml::Value::Float(required_attribute(property, "value")),
+                _ => toml::Value::String(String::from(text)),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+fn parse_tileset(map_path: &str, node: Node) -> Tileset {
+    let first_gid = required_attribute(node, "firstgid");
+    match node.attribute("source") {
+        Some(source) => {
+            let tileset_path = resolve_path(map_path, source);
+            let text = read_file(&tileset_path);
+            let document = Document::parse(&text).expect("Failed to parse tileset file");
+            tileset_from_node(first_gid, &tileset_path, document.root_element())
+        }
+        None => tileset_from_node(first_gid, map_path, node),
+    }
+}
+
+fn tileset_from_node(first_gid: u32, tileset_path: &str, node: Node) -> Tileset {
+    let image = child(node, "image").expect("Tilesets made of separate images are not supported");
+    let image_source: String = required_attribute(image, "source");
+    Tileset {
+        first_gid,
+        image_path: resolve_path(tileset_path, &image_source),
+        tile_width: required_attribute(node, "tilewidth"),
+        tile_height: required_attribute(node, "tileheight"),
+        columns: required_attribute(node, "columns"),
+        margin: attribute(node, "margin").unwrap_or(0),
+        spacing: attribute(node, "spacing").unwrap_or(0),
+        tile_count: required_attribute(node, "tilecount"),
+    }
+}
+
+fn parse_tile_layer(node: Node) -> TileLayer {
+    let name: String = required_attribute(node, "name");
+    let data = child(node, "data").expect("Tile layer has no data");
+    if data.attribute("encoding") != Some("csv") || data.has_attribute("compression") {
+        unsupported_encoding(&name);
+    }
+    let data = data
+        .text()
+        .unwrap_or("")
+        .split(',')
+        .map(|cell| cell.trim().parse().expect("Invalid tile"))
+        .collect();
+    TileLayer {
+        parallax: parallax(
+            &name,
+            attribute(node, "parallaxx").unwrap_or(1.0),
+            attribute(node, "parallaxy").unwrap_or(1.0),
+        ),
+        visible: attribute::<u8>(node, "visible").unwrap_or(1) != 0,
+        opacity: attribute(node, "opacity").unwrap_or(1.0),
+        properties: parse_properties(node),
+        name,
+        data,
+    }
+}
+
+// Points are stored as `x,y` pairs separated by spaces.
+fn parse_points(node: Node) -> Vec<[f32; 2]> {
+    let points: String = required_attribute(node, "points");
+    points
+        .split_whitespace()
+        .map(|point| {
+            let mut coords = point
+                .split(',')
+                .map(|coord| coord.parse().expect("Invalid point"));
+            match (coords.next(), coords.next(), coords.next()) {
+                (Some(x), Some(y), None) => [x, y],
+                _ => panic!("Invalid point: `{}`", point),
+            }
+        })
+        .collect()
+}
+
+fn parse_object_layer(node: Node) -> ObjectLayer {
+    ObjectLayer {
+        objects: node
+            .children()
+            .filter(|object| object.has_tag_name("object"))
+            .map(|object| Object {
+                name: attribute(object, "name").unwrap_or_default(),
+                kind: attribute(object, "type")
+                    .or_else(|| attribute(object, "class"))
+                    .unwrap_or_default(),
+                x: required_attribute(object, "x"),
+                y: required_attribute(object, "y"),
+                width: attribute(object, "width").unwrap_or(0.0),
+                height: attribute(object, "height").unwrap_or(0.0),
+                polygon: child(object, "polygon").map(parse_points),
+                properties: parse_properties(object),
+            })
+            .collect(),
+    }
+}
+
+pub fn load_map(full_path: &str) -> Map {
+    let text = read_file(full_path);
+    let document = Document::parse(&text).expect("Failed to parse Tiled map file");
+    let map = document.root_element();
+    if attribute::<u8>(map, "infinite").unwrap_or(0) != 0 {
+        panic!("Infinite Tiled maps are not supported");
+    }
+    if map.attribute("orientation") != Some("orthogonal") {
+        panic!("Only orthogonal Tiled maps are supported");
+    }
+
+    let mut tilesets = Vec::new();
+    let mut layers = Vec::new();
+    for node in map.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "tileset" => tilesets.push(parse_tileset(full_path, node)),
+            "layer" => layers.push(Layer::Tiles(parse_tile_layer(node))),
+            "objectgroup" => layers.push(Layer::Objects(parse_object_layer(node))),
+            "imagelayer" | "group" => panic!(
+                "Layer `{}` is an image layer or a group, which are not supported",
+                node.attribute("name").unwrap_or("")
+            ),
+            _ => {}
+        }
+    }
+
+    Map {
+        width: required_attribute(map, "width"),
+        height: required_attribute(map, "height"),
+        tile_width: required_attribute(map, "tilewidth"),
+        tile_height: required_attribute(map, "tileheight"),
+        tilesets,
+        layers,
+    }
+}